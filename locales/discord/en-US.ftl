@@ -1,10 +1,92 @@
 # command
 join = join
     .description = Start the text-to-speech session.
+    .mode = mode
+    .mode-description = Which chat to read (defaults to this channel)
 
 leave = leave
     .description = Stop the text-to-speech session.
 
+follow = follow
+    .description = Move the bot along with a user between voice channels.
+    .user = user
+    .user-description = User to follow (defaults to you)
+
+unfollow = unfollow
+    .description = Stop moving the bot along with a user.
+
+skip = skip
+    .description = Skip the message being read now.
+
+stop = stop
+    .description = Stop reading and clear all waiting messages.
+
+pause = pause
+    .description = Pause reading. Messages keep waiting until resumed.
+
+resume = resume
+    .description = Resume paused reading.
+
+volume = volume
+    .description = Change the reading volume of this server.
+    .level = level
+    .level-description = Volume in percent (0-200)
+
+add-channel = add-channel
+    .description = Also read messages of another channel in this session.
+    .channel = channel
+    .channel-description = Channel to read
+
+remove-channel = remove-channel
+    .description = Stop reading a channel added to this session.
+    .channel = channel
+    .channel-description = Channel to stop reading
+
+settings-channel-name = channel-name
+    .description = Say the channel name of messages from added channels and threads.
+    .enabled = enabled
+    .enabled-description = Whether to say the channel name
+
+settings-reply = reply
+    .description = Say who a reply is replying to.
+    .enabled = enabled
+    .enabled-description = Whether to say the author of the replied message
+
+settings-speaker-name = speaker-name
+    .description = Choose when the name of the speaker is read.
+    .policy = policy
+    .policy-description = When to read the name
+    .silence = silence
+    .silence-description = Seconds of silence before the name is read again
+
+settings-idle-timeout = idle-timeout
+    .description = Leave the voice channel when no message is read for a while.
+    .minutes = minutes
+    .minutes-description = Minutes without any message before leaving (0 to disable)
+
+settings-describe = describe
+    .description = Choose whether attachments, stickers, polls and embeds are read.
+    .category = category
+    .category-description = Kind of content to describe
+    .enabled = enabled
+    .enabled-description = Whether to describe it
+
+settings-join-leave = join-leave
+    .description = Choose how joining and leaving the voice channel is announced.
+    .enabled = enabled
+    .enabled-description = Whether to announce joining and leaving
+    .ignore-bots = ignore-bots
+    .ignore-bots-description = Whether to skip bots
+    .debounce = debounce
+    .debounce-description = Seconds to wait before announcing a leave, skipping quick rejoins (0 to disable)
+
+settings-join-leave-role = join-leave-role
+    .description = Announce joining and leaving only for members with the chosen roles.
+    .role = role
+    .role-description = Role to add or remove
+    .enabled = enabled
+    .enabled-description = Whether members with this role are announced (everyone is announced when no role is chosen)
+
 voice-user-choose = choose
     .description = Select your reading voice.
     .name = voice
@@ -28,6 +110,36 @@ join-response = 🚀 TTS started
 leave-response = 👋 TTS Ended
     .thanks = 💖 Thanks for using the bot!
 
+follow-response = 🚶 Following
+    .user = 👤 User
+
+unfollow-response = 🛑 Stopped following
+    .description = The bot stays in the current voice channel.
+
+skip-response = ⏭️ Skipped
+    .description = The message being read was skipped.
+
+stop-response = ⏹️ Stopped
+    .description = All waiting messages were cleared.
+
+pause-response = ⏸️ Paused
+    .description = Messages keep waiting until you run /resume.
+
+resume-response = ▶️ Resumed
+    .description = Waiting messages will be read.
+
+volume-response = 🔊 Volume Changed
+    .level = 🎚️ Volume
+
+add-channel-response = ➕ Channel Added
+    .channel = 📝 Reading channel
+
+remove-channel-response = ➖ Channel Removed
+    .channel = 📝 Channel
+
+settings-response = ⚙️ Successfully Configured!
+    .description = Reading settings of this server were updated.
+
 voice-choose-response = ⚙️ Successfully Configured!
     .description-user = Your voice was changed
     .description-guild = Guild standard voice was changed
@@ -46,3 +158,19 @@ link-response = ⚙️ Successfully Linked!
 unlink-response = 🧹 Successfully Unlinked!
     .description = TTS is now disabled
 
+# choice
+text-channel = this channel
+voice-chat = voice channel chat
+both = both
+always = always
+on-change = when the speaker changes
+on-change-or-silence = when the speaker changes or after silence
+never = never
+attachments = attachments
+stickers = stickers
+polls = polls
+embeds = embeds
+
+# notice
+messages-dropped-notice = ⚠️ Reading fell behind, so { $count } waiting messages were dropped.
+reconnect-failed-notice = ⚠️ Lost connection to the voice channel and could not rejoin after { $attempts } attempts, so reading has ended.
@@ -1,10 +1,92 @@
 # command
 join = join
     .description = 読み上げを開始します
+    .mode = 読み上げ対象
+    .mode-description = 読み上げるチャット (既定はこのチャンネル)
 
 leave = leave
     .description = 読み上げを終了します
 
+follow = follow
+    .description = ユーザーのボイスチャンネル移動にボットが付いていきます
+    .user = ユーザー
+    .user-description = 付いていくユーザー (既定は自分)
+
+unfollow = unfollow
+    .description = ユーザーへの追従をやめます
+
+skip = skip
+    .description = 読み上げ中のメッセージをスキップします
+
+stop = stop
+    .description = 読み上げを止めて待機中のメッセージをすべて破棄します
+
+pause = pause
+    .description = 読み上げを一時停止します。再開するまでメッセージは待機します
+
+resume = resume
+    .description = 一時停止した読み上げを再開します
+
+volume = volume
+    .description = サーバーの読み上げ音量を変更します
+    .level = 音量
+    .level-description = 音量 (0〜200%)
+
+add-channel = add-channel
+    .description = このセッションで別のチャンネルも読み上げます
+    .channel = チャンネル
+    .channel-description = 読み上げるチャンネル
+
+remove-channel = remove-channel
+    .description = 追加したチャンネルの読み上げをやめます
+    .channel = チャンネル
+    .channel-description = 読み上げをやめるチャンネル
+
+settings-channel-name = channel-name
+    .description = 追加したチャンネルやスレッドのメッセージでチャンネル名を読み上げます
+    .enabled = 有効
+    .enabled-description = チャンネル名を読み上げるかどうか
+
+settings-reply = reply
+    .description = 返信先のユーザー名を読み上げます
+    .enabled = 有効
+    .enabled-description = 返信先の投稿者名を読み上げるかどうか
+
+settings-speaker-name = speaker-name
+    .description = 発言者の名前を読み上げるタイミングを選択します
+    .policy = タイミング
+    .policy-description = 名前を読み上げるタイミング
+    .silence = 無音秒数
+    .silence-description = 名前を再び読み上げるまでの無音の秒数
+
+settings-idle-timeout = idle-timeout
+    .description = しばらくメッセージがないときにボイスチャンネルから退出します
+    .minutes = 分数
+    .minutes-description = 退出するまでの無発言の分数 (0で無効)
+
+settings-describe = describe
+    .description = 添付ファイル・スタンプ・投票・埋め込みを読み上げるかを選択します
+    .category = 種類
+    .category-description = 読み上げる内容の種類
+    .enabled = 有効
+    .enabled-description = 読み上げるかどうか
+
+settings-join-leave = join-leave
+    .description = ボイスチャンネルへの参加・退出の読み上げを設定します
+    .enabled = 有効
+    .enabled-description = 参加・退出を読み上げるかどうか
+    .ignore-bots = bot無視
+    .ignore-bots-description = botの参加・退出を読み上げないかどうか
+    .debounce = 待機秒数
+    .debounce-description = 退出を読み上げるまで待つ秒数。その間に再参加したら読み上げません (0で無効)
+
+settings-join-leave-role = join-leave-role
+    .description = 指定したロールを持つメンバーの参加・退出のみを読み上げます
+    .role = ロール
+    .role-description = 追加または削除するロール
+    .enabled = 有効
+    .enabled-description = このロールのメンバーを読み上げるかどうか (ロール未指定なら全員を読み上げます)
+
 voice-user-choose = choose
     .description = 読み上げボイスを選択します
     .name = ボイス名
@@ -28,6 +110,36 @@ join-response = 🚀 読み上げ開始
 leave-response = 👋 読み上げ終了
     .thanks = 💖 ご利用ありがとうございました！
 
+follow-response = 🚶 追従開始
+    .user = 👤 ユーザー
+
+unfollow-response = 🛑 追従終了
+    .description = 現在のボイスチャンネルに留まります
+
+skip-response = ⏭️ スキップ
+    .description = 読み上げ中のメッセージをスキップ
+
+stop-response = ⏹️ 停止
+    .description = 待機中のメッセージを破棄
+
+pause-response = ⏸️ 一時停止
+    .description = /resume で再開するまでメッセージは待機します
+
+resume-response = ▶️ 再開
+    .description = 待機中のメッセージを読み上げます
+
+volume-response = 🔊 音量変更
+    .level = 🎚️ 音量
+
+add-channel-response = ➕ チャンネル追加
+    .channel = 📝 読み上げチャンネル
+
+remove-channel-response = ➖ チャンネル削除
+    .channel = 📝 チャンネル
+
+settings-response = ⚙️ 設定完了
+    .description = サーバーの読み上げ設定を更新
+
 voice-choose-response = ⚙️ 設定完了
     .description-user = 読み上げボイスを更新
     .description-guild = サーバーの標準ボイスを更新
@@ -45,3 +157,20 @@ link-response = ⚙️ リンク完了
 
 unlink-response = 🧹 リンク解除
     .description = 読み上げ機能を無効化
+
+# choice
+text-channel = このチャンネル
+voice-chat = ボイスチャンネルのチャット
+both = 両方
+always = 常に
+on-change = 発言者が変わったとき
+on-change-or-silence = 発言者が変わったときか無音の後
+never = 読み上げない
+attachments = 添付ファイル
+stickers = スタンプ
+polls = 投票
+embeds = 埋め込み
+
+# notice
+messages-dropped-notice = ⚠️ 読み上げが追いつかないため、待機中のメッセージを{ $count }件破棄しました
+reconnect-failed-notice = ⚠️ ボイスチャンネルとの接続が切れ、{ $attempts }回再接続を試みましたが失敗したため読み上げを終了しました
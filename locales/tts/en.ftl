@@ -1,4 +1,19 @@
 # voice locales
 launch = Text-to-Speech session started.
 user-join = { $user } has joined the channel.
-user-leave = { $user } has left the channel.
\ No newline at end of file
+user-leave = { $user } has left the channel.
+message-in-channel = In { $channel }, { $text }
+message-reply = Replying to { $user }, { $text }
+speaker-name = { $user } says
+idle-leave = Leaving since no one has spoken for a while.
+attachment-image = { $count ->
+    [one] image attached
+   *[other] { $count } images attached
+}
+attachment-file = { $count ->
+    [one] file attached
+   *[other] { $count } files attached
+}
+sticker = sent a sticker: { $name }
+poll = poll: { $question }
+embed = embed: { $title }
@@ -2,3 +2,12 @@
 launch = 読み上げを開始します
 user-join = { $user }が参加しました
 user-leave = { $user }が退出しました
+message-in-channel = { $channel }から、{ $text }
+message-reply = { $user }への返信、{ $text }
+speaker-name = { $user }さん
+idle-leave = しばらく発言がないため退出します
+attachment-image = 画像{ $count }枚
+attachment-file = ファイル{ $count }件
+sticker = スタンプ、{ $name }
+poll = 投票、{ $question }
+embed = 埋め込み、{ $title }
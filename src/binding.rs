@@ -1,3 +1,4 @@
+use crate::session::ReadingMode;
 use poise::serenity_prelude::{ChannelId, GuildId};
 use redb::{Database, ReadableDatabase, TableDefinition};
 use std::sync::Arc;
@@ -6,20 +7,28 @@ use std::sync::Arc;
 /// guild_id -> (voice_channel_id, text_channel_id)
 const BINDINGS_TABLE: TableDefinition<u64, (u64, u64)> = TableDefinition::new("bindings");
 
+/// Table schema:
+/// guild_id -> reading mode
+///
+/// Kept apart from [`BINDINGS_TABLE`] so that bindings saved by older versions keep loading.
+const BINDING_MODES_TABLE: TableDefinition<u64, u8> = TableDefinition::new("binding_modes");
+
 pub struct Binding {
     pub voice: ChannelId,
     pub text: ChannelId,
+    pub mode: ReadingMode,
 }
 
 impl Binding {
-    pub fn new(voice: ChannelId, text: ChannelId) -> Self {
-        Self { voice, text }
+    pub fn new(voice: ChannelId, text: ChannelId, mode: ReadingMode) -> Self {
+        Self { voice, text, mode }
     }
 
     pub fn from_tuple(tuple: (u64, u64)) -> Self {
         Self {
             voice: ChannelId::new(tuple.0),
             text: ChannelId::new(tuple.1),
+            mode: ReadingMode::default(),
         }
     }
 
@@ -43,9 +52,20 @@ impl BindingRepository {
             Ok(table) => table,
             Err(_) => return Ok(None),
         };
-        Ok(table
+        let Some(mut binding) = table
             .get(guild.get())?
-            .map(|binding| Binding::from_tuple(binding.value())))
+            .map(|binding| Binding::from_tuple(binding.value()))
+        else {
+            return Ok(None);
+        };
+
+        if let Ok(modes) = tx.open_table(BINDING_MODES_TABLE)
+            && let Some(mode) = modes.get(guild.get())?
+        {
+            binding.mode = ReadingMode::from_u8(mode.value());
+        }
+
+        Ok(Some(binding))
     }
 
     pub async fn save_binding(&self, guild: GuildId, binding: Binding) -> anyhow::Result<()> {
@@ -54,6 +74,8 @@ impl BindingRepository {
         tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
             let tx = db.begin_write()?;
             {
+                let mut modes = tx.open_table(BINDING_MODES_TABLE)?;
+                modes.insert(guild.get(), binding.mode.to_u8())?;
                 let mut table = tx.open_table(BINDINGS_TABLE)?;
                 table.insert(guild.get(), binding.into_tuple())?;
             }
@@ -73,6 +95,8 @@ impl BindingRepository {
             {
                 let mut table = tx.open_table(BINDINGS_TABLE)?;
                 table.remove(guild.get())?;
+                let mut modes = tx.open_table(BINDING_MODES_TABLE)?;
+                modes.remove(guild.get())?;
             }
             tx.commit()?;
             Ok(())
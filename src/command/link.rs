@@ -1,12 +1,14 @@
 use crate::binding::Binding;
 use crate::command::{Context, Result};
+use crate::session::ReadingMode;
 use poise::CreateReply;
 use poise::serenity_prelude::{CreateEmbed, GuildChannel, Mentionable};
 
 #[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
 pub async fn link(
     ctx: Context<'_>,
-    #[channel_types("Voice")] voice_channel: GuildChannel,
+    #[channel_types("Voice", "Stage")] voice_channel: GuildChannel,
+    mode: Option<ReadingMode>,
 ) -> Result<()> {
     let guild_id = ctx
         .guild_id()
@@ -16,7 +18,10 @@ pub async fn link(
     let voice_channel_id = voice_channel.id;
     ctx.data()
         .binding_repository
-        .save_binding(guild_id, Binding::new(voice_channel_id, text_channel_id))
+        .save_binding(
+            guild_id,
+            Binding::new(voice_channel_id, text_channel_id, mode.unwrap_or_default()),
+        )
         .await?;
 
     let discord_locales = &ctx.data().discord_locales;
@@ -2,11 +2,22 @@ mod link;
 mod moderation;
 mod profile;
 mod session;
+mod settings;
 
 pub fn commands() -> Vec<poise::Command<crate::handler::Data, Error>> {
     vec![
         session::join(),
         session::leave(),
+        session::follow(),
+        session::unfollow(),
+        session::skip(),
+        session::stop(),
+        session::pause(),
+        session::resume(),
+        session::add_channel(),
+        session::remove_channel(),
+        settings::volume(),
+        settings::settings(),
         link::link(),
         link::unlink(),
         moderation::register(),
@@ -1,73 +1,52 @@
 use crate::command::{Context, Result};
-use crate::session::actor::SessionActor;
-use crate::session::driver::SongbirdDriver;
-use anyhow::Context as _;
+use crate::session::ReadingMode;
+use crate::usecase;
 use poise::CreateReply;
-use poise::serenity_prelude::{ChannelId, CreateEmbed, Mentionable};
-use std::sync::Arc;
+use poise::serenity_prelude::{ChannelId, CreateEmbed, GuildChannel, Mentionable, User};
 
 fn user_voice_channel_id(ctx: &Context<'_>) -> Result<ChannelId> {
+    voice_channel_of(ctx, ctx.author())?
+        .ok_or_else(|| anyhow::anyhow!("You have to join a voice channel to start text-to-speech"))
+}
+
+fn voice_channel_of(ctx: &Context<'_>, user: &User) -> Result<Option<ChannelId>> {
     let channel_id = ctx
         .guild()
         .ok_or_else(|| anyhow::anyhow!("Guild not found"))?
         .voice_states
-        .get(&ctx.author().id)
-        .and_then(|vs| vs.channel_id)
-        .ok_or_else(|| {
-            anyhow::anyhow!("You have to join a voice channel to start text-to-speech")
-        })?;
+        .get(&user.id)
+        .and_then(|vs| vs.channel_id);
     Ok(channel_id)
 }
 
 #[poise::command(slash_command, guild_only)]
-pub async fn join(ctx: Context<'_>) -> Result<()> {
+pub async fn join(ctx: Context<'_>, mode: Option<ReadingMode>) -> Result<()> {
     let guild_id = ctx
         .guild_id()
         .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
 
     let channel_id = user_voice_channel_id(&ctx)?;
+    let mode = mode.unwrap_or_default();
 
-    let manager = songbird::get(ctx.serenity_context())
-        .await
-        .ok_or_else(|| anyhow::anyhow!("Songbird Voice client not initialized"))?
-        .clone();
-
-    let handler = manager
-        .join(guild_id, channel_id)
-        .await
-        .context("Failed to join voice channel")?;
-
-    // prepare session actor to start text-to-speech
-    let driver = SongbirdDriver { call: handler };
-    let (actor, handle) = SessionActor::new(Arc::new(driver));
-
-    tokio::spawn(actor.run());
-
-    ctx.data()
-        .session_manager
-        .register(guild_id, ctx.channel_id(), channel_id, handle.clone());
-
-    let profile = ctx
-        .data()
-        .resolver
-        .resolve_guild_with_fallback(guild_id)
-        .await;
-
-    let profile_str = match &profile {
-        Ok(profile) => profile.id.as_str(),
-        Err(_) => ctx.data().resolver.fallback(),
-    };
-
-    let voice = ctx.data().registry.get_voice(profile_str).unwrap();
+    usecase::session::start(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild_id,
+        ctx.channel_id(),
+        channel_id,
+        mode,
+    )
+    .await?;
 
-    handle
-        .announce(
-            ctx.data()
-                .tts_locales
-                .resolve(voice.language(), "launch", None, None)?,
-            voice,
-        )
-        .await?;
+    let reading_channels = [
+        mode.reads_text_channel().then_some(ctx.channel_id()),
+        mode.reads_voice_chat().then_some(channel_id),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|channel| channel.mention().to_string())
+    .collect::<Vec<_>>()
+    .join(" ");
 
     let discord_locales = &ctx.data().discord_locales;
     let locale = ctx.locale().expect("must be some when slash command");
@@ -82,7 +61,7 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
                         Some("reading-channel"),
                         None,
                     )?,
-                    ctx.channel_id().mention().to_string(),
+                    reading_channels,
                     true,
                 )
                 .field(
@@ -134,3 +113,295 @@ pub async fn leave(ctx: Context<'_>) -> Result<()> {
 
     Ok(())
 }
+
+/// Move the bot along with a user, starting a session if none is running
+#[poise::command(slash_command, guild_only)]
+pub async fn follow(ctx: Context<'_>, user: Option<User>) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let channel_id = match &user {
+        Some(user) => voice_channel_of(&ctx, user)?
+            .ok_or_else(|| anyhow::anyhow!("{} is not in a voice channel", user.name))?,
+        None => user_voice_channel_id(&ctx)?,
+    };
+    let user = user.as_ref().unwrap_or(ctx.author());
+
+    match ctx.data().session_manager.get(guild_id) {
+        Some(session) if session.voice_channel != channel_id => {
+            usecase::session::move_to(ctx.serenity_context(), guild_id, channel_id).await?;
+        }
+        Some(_) => {}
+        None => {
+            usecase::session::start(
+                ctx.serenity_context(),
+                ctx.data(),
+                guild_id,
+                ctx.channel_id(),
+                channel_id,
+                ReadingMode::default(),
+            )
+            .await?;
+        }
+    }
+
+    ctx.data()
+        .session_manager
+        .set_followed(guild_id, Some(user.id))
+        .await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "follow-response", None, None)?)
+                .field(
+                    discord_locales.resolve(locale, "follow-response", Some("user"), None)?,
+                    user.mention().to_string(),
+                    true,
+                ),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Stop moving the bot along with a user
+#[poise::command(slash_command, guild_only)]
+pub async fn unfollow(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .session_manager
+        .set_followed(guild_id, None)
+        .await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "unfollow-response", None, None)?)
+                .description(discord_locales.resolve(
+                    locale,
+                    "unfollow-response",
+                    Some("description"),
+                    None,
+                )?),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+pub async fn skip(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let session = ctx
+        .data()
+        .session_manager
+        .get(guild_id)
+        .ok_or(anyhow::anyhow!("Guild not found"))?;
+
+    session.handle.skip().await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "skip-response", None, None)?)
+                .description(discord_locales.resolve(
+                    locale,
+                    "skip-response",
+                    Some("description"),
+                    None,
+                )?),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+pub async fn stop(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let session = ctx
+        .data()
+        .session_manager
+        .get(guild_id)
+        .ok_or(anyhow::anyhow!("Guild not found"))?;
+
+    session.handle.stop().await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "stop-response", None, None)?)
+                .description(discord_locales.resolve(
+                    locale,
+                    "stop-response",
+                    Some("description"),
+                    None,
+                )?),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+pub async fn pause(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let session = ctx
+        .data()
+        .session_manager
+        .get(guild_id)
+        .ok_or(anyhow::anyhow!("Guild not found"))?;
+
+    session.handle.pause().await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "pause-response", None, None)?)
+                .description(discord_locales.resolve(
+                    locale,
+                    "pause-response",
+                    Some("description"),
+                    None,
+                )?),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only)]
+pub async fn resume(ctx: Context<'_>) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let session = ctx
+        .data()
+        .session_manager
+        .get(guild_id)
+        .ok_or(anyhow::anyhow!("Guild not found"))?;
+
+    session.handle.resume().await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "resume-response", None, None)?)
+                .description(discord_locales.resolve(
+                    locale,
+                    "resume-response",
+                    Some("description"),
+                    None,
+                )?),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "add-channel")]
+pub async fn add_channel(
+    ctx: Context<'_>,
+    #[channel_types("Text", "News")] channel: GuildChannel,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .session_manager
+        .add_text_channel(guild_id, channel.id)
+        .await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "add-channel-response", None, None)?)
+                .field(
+                    discord_locales.resolve(
+                        locale,
+                        "add-channel-response",
+                        Some("channel"),
+                        None,
+                    )?,
+                    channel.id.mention().to_string(),
+                    true,
+                ),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, guild_only, rename = "remove-channel")]
+pub async fn remove_channel(
+    ctx: Context<'_>,
+    #[channel_types("Text", "News")] channel: GuildChannel,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .session_manager
+        .remove_text_channel(guild_id, channel.id)
+        .await?;
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "remove-channel-response", None, None)?)
+                .field(
+                    discord_locales.resolve(
+                        locale,
+                        "remove-channel-response",
+                        Some("channel"),
+                        None,
+                    )?,
+                    channel.id.mention().to_string(),
+                    true,
+                ),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use crate::command::{Context, Result};
+use crate::session::SpeakerNamePolicy;
+use crate::settings::DescriptionCategory;
+use poise::CreateReply;
+use poise::serenity_prelude::{CreateEmbed, Role};
+
+/// Change reading volume of the guild
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn volume(
+    ctx: Context<'_>,
+    #[min = 0]
+    #[max = 200]
+    level: u16,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let settings = ctx
+        .data()
+        .settings_repository
+        .update(guild_id, move |settings| settings.volume = level)
+        .await?;
+
+    if let Some(session) = ctx.data().session_manager.get(guild_id) {
+        session.handle.set_volume(settings.volume_ratio()).await?;
+    }
+
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "volume-response", None, None)?)
+                .field(
+                    discord_locales.resolve(locale, "volume-response", Some("level"), None)?,
+                    format!("{}%", settings.volume),
+                    true,
+                ),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Reading settings of the guild
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "channel_name",
+        "reply",
+        "speaker_name",
+        "idle_timeout",
+        "describe",
+        "join_leave",
+        "join_leave_role"
+    ),
+    subcommand_required,
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn settings(_: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Say the channel name of messages read from channels added with add-channel
+#[poise::command(
+    slash_command,
+    rename = "channel-name",
+    identifying_name = "settings-channel-name"
+)]
+pub async fn channel_name(ctx: Context<'_>, enabled: bool) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .settings_repository
+        .update(guild_id, move |settings| {
+            settings.announce_channel_name = enabled
+        })
+        .await?;
+
+    reply_updated(ctx).await
+}
+
+/// Say the author of the message replied to
+#[poise::command(slash_command, rename = "reply", identifying_name = "settings-reply")]
+pub async fn reply(ctx: Context<'_>, enabled: bool) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .settings_repository
+        .update(guild_id, move |settings| settings.announce_reply = enabled)
+        .await?;
+
+    reply_updated(ctx).await
+}
+
+/// Choose when the name of the speaker is read
+#[poise::command(
+    slash_command,
+    rename = "speaker-name",
+    identifying_name = "settings-speaker-name"
+)]
+pub async fn speaker_name(
+    ctx: Context<'_>,
+    policy: SpeakerNamePolicy,
+    #[min = 1]
+    #[max = 3600]
+    silence: Option<u64>,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let settings = ctx
+        .data()
+        .settings_repository
+        .update(guild_id, move |settings| {
+            settings.speaker_name = policy;
+            if let Some(silence) = silence {
+                settings.speaker_name_silence_secs = silence;
+            }
+        })
+        .await?;
+
+    if let Some(session) = ctx.data().session_manager.get(guild_id) {
+        session
+            .handle
+            .set_name_announcement(settings.name_announcement())
+            .await?;
+    }
+
+    reply_updated(ctx).await
+}
+
+/// Leave the voice channel after minutes without any message
+#[poise::command(
+    slash_command,
+    rename = "idle-timeout",
+    identifying_name = "settings-idle-timeout"
+)]
+pub async fn idle_timeout(
+    ctx: Context<'_>,
+    #[min = 0]
+    #[max = 1440]
+    minutes: u64,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    let settings = ctx
+        .data()
+        .settings_repository
+        .update(guild_id, move |settings| {
+            settings.idle_timeout_minutes = minutes
+        })
+        .await?;
+
+    if let Some(session) = ctx.data().session_manager.get(guild_id) {
+        session
+            .handle
+            .set_idle_timeout(settings.idle_timeout())
+            .await?;
+    }
+
+    reply_updated(ctx).await
+}
+
+/// Choose whether attachments, stickers, polls and embeds are described
+#[poise::command(
+    slash_command,
+    rename = "describe",
+    identifying_name = "settings-describe"
+)]
+pub async fn describe(
+    ctx: Context<'_>,
+    category: DescriptionCategory,
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .settings_repository
+        .update(guild_id, move |settings| {
+            settings.descriptions.set(category, enabled)
+        })
+        .await?;
+
+    reply_updated(ctx).await
+}
+
+/// Choose how joining and leaving the voice channel is announced
+#[poise::command(
+    slash_command,
+    rename = "join-leave",
+    identifying_name = "settings-join-leave"
+)]
+pub async fn join_leave(
+    ctx: Context<'_>,
+    enabled: bool,
+    #[rename = "ignore-bots"] ignore_bots: Option<bool>,
+    #[min = 0]
+    #[max = 600]
+    debounce: Option<u64>,
+) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .settings_repository
+        .update(guild_id, move |settings| {
+            let notifications = &mut settings.voice_notifications;
+            notifications.enabled = enabled;
+            if let Some(ignore_bots) = ignore_bots {
+                notifications.ignore_bots = ignore_bots;
+            }
+            if let Some(debounce) = debounce {
+                notifications.debounce_secs = debounce;
+            }
+        })
+        .await?;
+
+    reply_updated(ctx).await
+}
+
+/// Announce joining and leaving only for members with the chosen roles
+#[poise::command(
+    slash_command,
+    rename = "join-leave-role",
+    identifying_name = "settings-join-leave-role"
+)]
+pub async fn join_leave_role(ctx: Context<'_>, role: Role, enabled: bool) -> Result<()> {
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| anyhow::anyhow!("Guild only"))?;
+
+    ctx.data()
+        .settings_repository
+        .update(guild_id, move |settings| {
+            settings.voice_notifications.set_role(role.id, enabled)
+        })
+        .await?;
+
+    reply_updated(ctx).await
+}
+
+async fn reply_updated(ctx: Context<'_>) -> Result<()> {
+    let discord_locales = &ctx.data().discord_locales;
+    let locale = ctx.locale().expect("must be some when slash command");
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .title(discord_locales.resolve(locale, "settings-response", None, None)?)
+                .description(discord_locales.resolve(
+                    locale,
+                    "settings-response",
+                    Some("description"),
+                    None,
+                )?),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
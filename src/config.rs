@@ -1,11 +1,16 @@
+use crate::session::queue::DropPolicy;
 use crate::tts::VoiceDetail;
+use crate::tts::command::CommandVoiceConfig;
 use crate::tts::google_cloud::GoogleCloudVoiceConfig;
+use crate::tts::openai::OpenAiSpeechVoiceConfig;
+use crate::tts::piper::PiperVoiceConfig;
+use crate::tts::scheduler::ConcurrencyLimit;
 use crate::tts::voicevox::VoicevoxVoiceConfig;
 use anyhow::anyhow;
 use config::{Config, File};
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn load_config(path: &Path) -> anyhow::Result<AppConfig> {
     let config = Config::builder()
@@ -27,6 +32,12 @@ pub struct AppConfig {
 
     pub cache: CacheConfig,
 
+    #[serde(default)]
+    pub session: SessionConfig,
+
+    /// Outbound webhook receiving session events, disabled if absent.
+    pub webhook: Option<WebhookConfig>,
+
     pub profiles: HashMap<String, ProfileConfig>,
 }
 
@@ -36,6 +47,24 @@ impl AppConfig {
             return Err(anyhow!("bot token is empty"));
         }
 
+        self.backend.voicevox_engines()?;
+
+        if let Some(piper) = &self.backend.piper {
+            verify_timeout(piper.timeout, "[backend.piper]")?;
+        }
+        if let Some(command) = &self.backend.command {
+            verify_timeout(command.timeout, "[backend.command]")?;
+        }
+        for (id, profile) in &self.profiles {
+            if let ProfileBackendConfig::CommandVoice(c) = &profile.voice_backend {
+                if let Some(timeout) = c.timeout {
+                    verify_timeout(timeout, &format!("profile {}", id))?;
+                }
+                c.verify()
+                    .map_err(|e| anyhow!("invalid profile {}: {}", id, e))?;
+            }
+        }
+
         if !self.profiles.contains_key(&self.bot.global_profile) {
             return Err(anyhow!(
                 "No profile matched for {}, specified for global_profile",
@@ -47,6 +76,14 @@ impl AppConfig {
     }
 }
 
+/// Local programs are killed once the timeout passes, so no synthesis could ever finish with `0`.
+fn verify_timeout(timeout: u64, owner: &str) -> anyhow::Result<()> {
+    if timeout == 0 {
+        return Err(anyhow!("timeout of {} must be positive", owner));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct BotConfig {
     pub token: String,
@@ -71,6 +108,75 @@ pub struct DatabaseConfig {
 pub struct BackendConfig {
     pub google_cloud: Option<GoogleCloudBackendConfig>,
     pub voicevox: Option<VoicevoxBackendConfig>,
+    /// Engines speaking the VOICEVOX protocol, such as AivisSpeech, COEIROINK or SHAREVOX, by name.
+    #[serde(default)]
+    pub voicevox_compatible: HashMap<String, VoicevoxCompatibleBackendConfig>,
+    /// Engines serving the OpenAI `/v1/audio/speech` API, such as Kokoro-FastAPI or LocalAI, by name.
+    #[serde(default)]
+    pub openai_compatible: HashMap<String, OpenAiCompatibleBackendConfig>,
+    pub piper: Option<PiperBackendConfig>,
+    pub command: Option<CommandBackendConfig>,
+}
+
+impl BackendConfig {
+    /// Every enabled VOICEVOX-compatible engine by name.
+    ///
+    /// `[backend.voicevox]` is included as the engine named [`DEFAULT_VOICEVOX_ENGINE`].
+    pub fn voicevox_engines(
+        &self,
+    ) -> anyhow::Result<HashMap<String, VoicevoxCompatibleBackendConfig>> {
+        let mut engines: HashMap<_, _> = self
+            .voicevox_compatible
+            .iter()
+            .filter(|(_, engine)| engine.enabled)
+            .map(|(name, engine)| (name.clone(), engine.clone()))
+            .collect();
+
+        if let Some(voicevox) = self.voicevox.as_ref().filter(|c| c.enabled) {
+            if engines.contains_key(DEFAULT_VOICEVOX_ENGINE) {
+                return Err(anyhow!(
+                    "[backend.voicevox] and [backend.voicevox_compatible.{}] cannot be used together",
+                    DEFAULT_VOICEVOX_ENGINE
+                ));
+            }
+            engines.insert(
+                DEFAULT_VOICEVOX_ENGINE.to_string(),
+                VoicevoxCompatibleBackendConfig {
+                    enabled: true,
+                    url: voicevox.url.clone(),
+                    timeout: voicevox.timeout,
+                    provider: Some("VOICEVOX".to_string()),
+                    language: default_voicevox_language(),
+                    generate_profiles: voicevox.generate_profiles,
+                    initialize_speakers: voicevox.initialize_speakers,
+                    concurrency: voicevox.concurrency.clone(),
+                },
+            );
+        }
+
+        Ok(engines)
+    }
+}
+
+/// Engine used by VOICEVOX profiles which do not name one.
+pub const DEFAULT_VOICEVOX_ENGINE: &str = "voicevox";
+
+/// Limits of concurrent synthesis, shared by every backend section.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConcurrencyConfig {
+    /// Maximum number of requests in flight to the backend, across all guilds.
+    pub max_concurrency: Option<usize>,
+    /// Maximum number of requests in flight to the backend for a single guild.
+    pub max_concurrency_per_guild: Option<usize>,
+}
+
+impl ConcurrencyConfig {
+    pub fn limit(&self, default_total: usize) -> ConcurrencyLimit {
+        ConcurrencyLimit {
+            total: self.max_concurrency.unwrap_or(default_total),
+            per_guild: self.max_concurrency_per_guild.unwrap_or(2),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -78,24 +184,218 @@ pub struct GoogleCloudBackendConfig {
     pub enabled: bool,
     #[serde(default = "default_google_cloud_timeout")]
     pub timeout: u64,
+    #[serde(flatten)]
+    pub concurrency: ConcurrencyConfig,
 }
 
 fn default_google_cloud_timeout() -> u64 {
     5
 }
 
+impl GoogleCloudBackendConfig {
+    pub fn concurrency_limit(&self) -> ConcurrencyLimit {
+        self.concurrency.limit(16)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct VoicevoxBackendConfig {
     pub enabled: bool,
     pub url: String,
     #[serde(default = "default_voicevox_timeout")]
     pub timeout: u64,
+    #[serde(default)]
+    pub generate_profiles: bool,
+    #[serde(default)]
+    pub initialize_speakers: bool,
+    #[serde(flatten)]
+    pub concurrency: ConcurrencyConfig,
 }
 
 fn default_voicevox_timeout() -> u64 {
     30
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct VoicevoxCompatibleBackendConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub url: String,
+    #[serde(default = "default_voicevox_timeout")]
+    pub timeout: u64,
+    pub provider: Option<String>,
+    /// Language spoken by every voice of the engine.
+    #[serde(default = "default_voicevox_language")]
+    pub language: String,
+    /// Whether to add a profile for every style listed by `/speakers` at startup.
+    #[serde(default)]
+    pub generate_profiles: bool,
+    /// Whether to warm every generated style with `/initialize_speaker` at startup.
+    #[serde(default)]
+    pub initialize_speakers: bool,
+    #[serde(flatten)]
+    pub concurrency: ConcurrencyConfig,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Shown as the provider of the voices of a named engine: `provider` if configured, otherwise the engine name.
+fn engine_provider(provider: &Option<String>, name: &str) -> String {
+    provider.clone().unwrap_or_else(|| name.to_string())
+}
+
+fn default_voicevox_language() -> String {
+    "ja-JP".to_string()
+}
+
+impl VoicevoxCompatibleBackendConfig {
+    pub fn provider(&self, name: &str) -> String {
+        engine_provider(&self.provider, name)
+    }
+
+    pub fn concurrency_limit(&self) -> ConcurrencyLimit {
+        // a single engine synthesizes roughly one request per core
+        self.concurrency.limit(4)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAiCompatibleBackendConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// API root, usually ending with `/v1`.
+    pub url: String,
+    /// Sent as a bearer token if present.
+    pub api_key: Option<String>,
+    #[serde(default = "default_openai_compatible_timeout")]
+    pub timeout: u64,
+    pub provider: Option<String>,
+    #[serde(flatten)]
+    pub concurrency: ConcurrencyConfig,
+}
+
+fn default_openai_compatible_timeout() -> u64 {
+    30
+}
+
+impl OpenAiCompatibleBackendConfig {
+    pub fn provider(&self, name: &str) -> String {
+        engine_provider(&self.provider, name)
+    }
+
+    pub fn concurrency_limit(&self) -> ConcurrencyLimit {
+        self.concurrency.limit(4)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PiperBackendConfig {
+    pub enabled: bool,
+    /// Path of the piper executable, looked up in `PATH` if not absolute.
+    #[serde(default = "default_piper_binary")]
+    pub binary: PathBuf,
+    #[serde(default = "default_piper_timeout")]
+    pub timeout: u64,
+    #[serde(flatten)]
+    pub concurrency: ConcurrencyConfig,
+}
+
+fn default_piper_binary() -> PathBuf {
+    PathBuf::from("piper")
+}
+
+fn default_piper_timeout() -> u64 {
+    30
+}
+
+impl PiperBackendConfig {
+    pub fn concurrency_limit(&self) -> ConcurrencyLimit {
+        // every synthesis is a process using the CPU
+        self.concurrency.limit(2)
+    }
+}
+
+/// Local programs run by `command` profiles.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandBackendConfig {
+    pub enabled: bool,
+    /// Seconds to wait for a program, unless its profile sets `timeout`.
+    #[serde(default = "default_command_timeout")]
+    pub timeout: u64,
+    #[serde(flatten)]
+    pub concurrency: ConcurrencyConfig,
+}
+
+fn default_command_timeout() -> u64 {
+    30
+}
+
+impl CommandBackendConfig {
+    pub fn concurrency_limit(&self) -> ConcurrencyLimit {
+        self.concurrency.limit(2)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout: u64,
+}
+
+fn default_webhook_timeout() -> u64 {
+    10
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionConfig {
+    /// Maximum number of user messages waiting to be read per session.
+    #[serde(default = "default_max_backlog")]
+    pub max_backlog: usize,
+    /// What to drop when the backlog is full.
+    #[serde(default)]
+    pub drop_policy: DropPolicy,
+    /// Number of upcoming messages synthesized ahead of playback.
+    #[serde(default = "default_prefetch")]
+    pub prefetch: usize,
+    /// Number of attempts to rejoin the voice channel after the connection is lost.
+    #[serde(default = "default_reconnect_attempts")]
+    pub reconnect_attempts: usize,
+    /// Delay before the first attempt to rejoin, doubled on every failure.
+    #[serde(default = "default_reconnect_delay_ms")]
+    pub reconnect_delay_ms: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            max_backlog: default_max_backlog(),
+            drop_policy: DropPolicy::default(),
+            prefetch: default_prefetch(),
+            reconnect_attempts: default_reconnect_attempts(),
+            reconnect_delay_ms: default_reconnect_delay_ms(),
+        }
+    }
+}
+
+fn default_max_backlog() -> usize {
+    100
+}
+
+fn default_prefetch() -> usize {
+    3
+}
+
+fn default_reconnect_attempts() -> usize {
+    5
+}
+
+fn default_reconnect_delay_ms() -> u64 {
+    1000
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type")]
 pub enum CacheConfig {
@@ -145,6 +445,12 @@ pub enum ProfileBackendConfig {
     GoogleCloudVoice(GoogleCloudVoiceConfig),
     #[serde(rename = "voicevox")]
     VoicevoxVoice(VoicevoxVoiceConfig),
+    #[serde(rename = "openai_compatible")]
+    OpenAiSpeechVoice(OpenAiSpeechVoiceConfig),
+    #[serde(rename = "piper")]
+    PiperVoice(PiperVoiceConfig),
+    #[serde(rename = "command")]
+    CommandVoice(CommandVoiceConfig),
 }
 
 impl ProfileBackendConfig {
@@ -152,6 +458,9 @@ impl ProfileBackendConfig {
         match &self {
             ProfileBackendConfig::GoogleCloudVoice(config) => config.generate_default_detail(name),
             ProfileBackendConfig::VoicevoxVoice(config) => config.generate_default_detail(name),
+            ProfileBackendConfig::OpenAiSpeechVoice(config) => config.generate_default_detail(name),
+            ProfileBackendConfig::PiperVoice(config) => config.generate_default_detail(name),
+            ProfileBackendConfig::CommandVoice(config) => config.generate_default_detail(name),
         }
     }
 }
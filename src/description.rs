@@ -0,0 +1,167 @@
+use crate::localization::Locales;
+use crate::settings::DescriptionSettings;
+use fluent::fluent_args;
+use poise::serenity_prelude::Message;
+
+/// Spoken descriptions of what the message carries besides its text,
+/// such as attachments and stickers, in the order they are read.
+pub fn describe(
+    message: &Message,
+    settings: &DescriptionSettings,
+    locales: &Locales,
+    language: &str,
+) -> anyhow::Result<Vec<String>> {
+    let mut descriptions = Vec::new();
+
+    if settings.attachments {
+        let images = message
+            .attachments
+            .iter()
+            .filter(|attachment| {
+                attachment
+                    .content_type
+                    .as_deref()
+                    .is_some_and(|content_type| content_type.starts_with("image/"))
+            })
+            .count();
+        let files = message.attachments.len() - images;
+
+        for (id, count) in [("attachment-image", images), ("attachment-file", files)] {
+            if count > 0 {
+                descriptions.push(locales.resolve(
+                    language,
+                    id,
+                    None,
+                    Some(&fluent_args!["count" => count]),
+                )?);
+            }
+        }
+    }
+
+    if settings.stickers {
+        for sticker in &message.sticker_items {
+            descriptions.push(locales.resolve(
+                language,
+                "sticker",
+                None,
+                Some(&fluent_args!["name" => sticker.name.as_str()]),
+            )?);
+        }
+    }
+
+    if settings.polls
+        && let Some(poll) = &message.poll
+    {
+        let question = poll.question.text.clone().unwrap_or_default();
+        descriptions.push(locales.resolve(
+            language,
+            "poll",
+            None,
+            Some(&fluent_args!["question" => question]),
+        )?);
+    }
+
+    if settings.embeds {
+        // untitled embeds carry nothing worth reading
+        for title in message
+            .embeds
+            .iter()
+            .filter_map(|embed| embed.title.as_deref())
+        {
+            descriptions.push(locales.resolve(
+                language,
+                "embed",
+                None,
+                Some(&fluent_args!["title" => title]),
+            )?);
+        }
+    }
+
+    Ok(descriptions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::localization::load_tts_locales;
+    use serde_json::json;
+
+    fn message(value: serde_json::Value) -> Message {
+        let mut base = json!({
+            "id": "1",
+            "channel_id": "2",
+            "author": { "id": "3", "username": "user", "discriminator": "0000", "avatar": null },
+            "content": "",
+            "timestamp": "2024-01-01T00:00:00+00:00",
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "embeds": [],
+            "pinned": false,
+            "type": 0,
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(base).expect("must be a valid message")
+    }
+
+    /// Strips the bidi isolation marks fluent puts around placeables.
+    fn plain(descriptions: Vec<String>) -> Vec<String> {
+        descriptions
+            .into_iter()
+            .map(|description| description.replace(['\u{2068}', '\u{2069}'], ""))
+            .collect()
+    }
+
+    fn attachment(id: u64, content_type: &str) -> serde_json::Value {
+        json!({
+            "id": id.to_string(),
+            "filename": "file",
+            "size": 1,
+            "url": "https://example.com",
+            "proxy_url": "https://example.com",
+            "content_type": content_type,
+        })
+    }
+
+    #[test]
+    fn describes_images_and_files_separately() {
+        let locales = load_tts_locales("en").unwrap();
+        let message = message(json!({
+            "attachments": [
+                attachment(1, "image/png"),
+                attachment(2, "image/jpeg"),
+                attachment(3, "application/pdf"),
+            ],
+        }));
+
+        let descriptions =
+            describe(&message, &DescriptionSettings::default(), &locales, "en").unwrap();
+
+        assert_eq!(
+            plain(descriptions),
+            vec!["2 images attached", "file attached"]
+        );
+    }
+
+    #[test]
+    fn disabled_categories_are_not_described() {
+        let locales = load_tts_locales("en").unwrap();
+        let message = message(json!({
+            "attachments": [attachment(1, "image/png")],
+            "sticker_items": [{ "id": "4", "name": "wave", "format_type": 1 }],
+        }));
+        let settings = DescriptionSettings {
+            attachments: false,
+            ..Default::default()
+        };
+
+        let descriptions = describe(&message, &settings, &locales, "en").unwrap();
+
+        assert_eq!(plain(descriptions), vec!["sent a sticker: wave"]);
+    }
+}
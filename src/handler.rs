@@ -1,17 +1,23 @@
 use crate::binding::BindingRepository;
+use crate::config::SessionConfig;
 use crate::localization::Locales;
 use crate::profile::repository::ProfileRepository;
 use crate::profile::resolver::ProfileResolver;
-use crate::session::manager::SessionManager;
+use crate::session::event::EventBus;
+use crate::session::manager::{SessionInfo, SessionManager};
 use crate::session::{SessionHandle, Speaker};
+use crate::settings::GuildSettingsRepository;
+use crate::tts::Voice;
 use crate::tts::registry::VoicePackageRegistry;
-use crate::{text_preprocessor, usecase};
+use crate::voice_notification::LeaveDebouncer;
+use crate::{description, text_preprocessor, usecase};
 use anyhow::{Context, anyhow};
 use fluent::fluent_args;
 use poise::serenity_prelude as serenity;
 use poise::serenity_prelude::futures::future::join_all;
 use poise::serenity_prelude::{ChannelId, VoiceState};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct Data {
     pub session_manager: SessionManager,
@@ -19,8 +25,13 @@ pub struct Data {
     pub resolver: ProfileResolver,
     pub repository: Arc<dyn ProfileRepository>,
     pub tts_locales: Locales,
-    pub discord_locales: Locales,
+    pub discord_locales: Arc<Locales>,
     pub binding_repository: BindingRepository,
+    pub settings_repository: GuildSettingsRepository,
+    pub session_config: SessionConfig,
+    /// session events, for anything which wants to observe sessions
+    pub events: EventBus,
+    pub leave_debouncer: Arc<LeaveDebouncer>,
 }
 
 pub async fn event_handler(
@@ -34,6 +45,12 @@ pub async fn event_handler(
             tracing::info!("Ready: {}", data_about_bot.user.name);
         }
 
+        // voice states are not cached yet on Ready, so restore sessions once guilds are cached.
+        serenity::FullEvent::CacheReady { guilds } => {
+            tracing::info!("Cache ready: {} guilds", guilds.len());
+            restore_sessions(ctx, data).await?;
+        }
+
         serenity::FullEvent::VoiceStateUpdate { old, new } => {
             if new.user_id == ctx.cache.current_user().id {
                 match voice_state_update_kind(old, new) {
@@ -42,7 +59,15 @@ pub async fn event_handler(
                         new_channel_id,
                     } => {
                         data.session_manager
-                            .update_voice_channel(old_channel_id, new_channel_id)?;
+                            .update_voice_channel(old_channel_id, new_channel_id)
+                            .await?;
+
+                        // moved into a stage, where the bot joins the audience
+                        if let Some(guild_id) = new.guild_id
+                            && usecase::session::is_stage_channel(ctx, guild_id, new_channel_id)
+                        {
+                            usecase::session::become_speaker(ctx, guild_id, new_channel_id).await;
+                        }
                     }
                     ChannelTransition::Disconnect { old_channel_id: _ } => {
                         if let Some(guild_id) = old.as_ref().and_then(|state| state.guild_id) {
@@ -59,23 +84,12 @@ pub async fn event_handler(
                 Some(id) => id,
                 None => return Ok(()),
             };
-            let user_id = new.user_id;
 
-            for notification in generate_notifications(old, new) {
-                if let Some(session) = data
-                    .session_manager
-                    .get_by_voice_channel(notification.channel_id)
-                {
-                    send_session_notification(
-                        ctx,
-                        data,
-                        &session.handle,
-                        guild_id,
-                        user_id,
-                        notification.locale_id,
-                    )
-                    .await?;
-                }
+            // settled apart, so that neither an error below nor the debounce period holds it up
+            if let Some(deferred_leaves) =
+                announce_voice_transition(ctx, data, guild_id, old, new).await?
+            {
+                tokio::spawn(deferred_leaves.settle(data.leave_debouncer.clone()));
             }
 
             let handle_connection = async |new_channel_id: ChannelId| -> anyhow::Result<()> {
@@ -100,8 +114,15 @@ pub async fn event_handler(
                         });
 
                     if !has_other_members_in_room {
-                        usecase::session::start(ctx, data, guild_id, binding.text, binding.voice)
-                            .await?;
+                        usecase::session::start(
+                            ctx,
+                            data,
+                            guild_id,
+                            binding.text,
+                            binding.voice,
+                            binding.mode,
+                        )
+                        .await?;
                     }
                 }
 
@@ -114,25 +135,8 @@ pub async fn event_handler(
                     return Ok(());
                 };
 
-                let human_check_tasks = ctx
-                    .cache
-                    .guild(guild_id)
-                    .ok_or_else(|| anyhow!("guild not found"))?
-                    .voice_states
-                    .iter()
-                    .filter(|&(&user_id, voice_state)| {
-                        voice_state.channel_id == Some(old_channel_id) && user_id != new.user_id
-                    })
-                    .map(|(&user_id, _)| {
-                        let ctx = ctx.clone();
-                        async move { user_id.to_user(ctx).await.map(|u| !u.bot).unwrap_or(true) }
-                    })
-                    .collect::<Vec<_>>();
-
-                let has_other_members_in_room = join_all(human_check_tasks)
-                    .await
-                    .into_iter()
-                    .any(|is_human| is_human);
+                let has_other_members_in_room =
+                    has_humans_in_channel(ctx, guild_id, old_channel_id, Some(new.user_id)).await?;
 
                 // disconnect if all human members left voice channel.
                 if !has_other_members_in_room {
@@ -150,8 +154,17 @@ pub async fn event_handler(
                     new_channel_id,
                     old_channel_id,
                 } => {
-                    handle_disconnection(old_channel_id).await?;
-                    handle_connection(new_channel_id).await?;
+                    let followed = data
+                        .session_manager
+                        .get_by_voice_channel(old_channel_id)
+                        .is_some_and(|session| session.followed == Some(new.user_id));
+
+                    if followed {
+                        usecase::session::move_to(ctx, guild_id, new_channel_id).await?;
+                    } else {
+                        handle_disconnection(old_channel_id).await?;
+                        handle_connection(new_channel_id).await?;
+                    }
                 }
                 ChannelTransition::Disconnect { old_channel_id } => {
                     handle_disconnection(old_channel_id).await?;
@@ -166,64 +179,88 @@ pub async fn event_handler(
                 return Ok(());
             }
 
-            if let Some(session) = data
-                .session_manager
-                .get_by_text_channel(new_message.channel_id)
+            if let Some(session) =
+                find_reading_session(ctx, data, new_message.guild_id, new_message.channel_id)
             {
-                let profile = data
-                    .resolver
-                    .resolve_with_fallback(
-                        new_message.author.id,
-                        new_message
-                            .guild_id
-                            .ok_or(anyhow::anyhow!("Guild not found"))?,
-                    )
-                    .await;
-
-                let profile_str = match &profile {
-                    Ok(profile) => profile.id.as_str(),
-                    Err(_) => data.resolver.fallback(),
-                };
-
-                let voice = data
-                    .registry
-                    .get_voice(profile_str)
-                    .ok_or_else(|| anyhow::anyhow!("No voice preset found"))?;
-
                 let guild_id = new_message
                     .guild_id
                     .ok_or(anyhow::anyhow!("Message does not contain guild ID"))?;
 
-                let text = new_message.content.clone();
-                let text = text_preprocessor::normalize_mentions(
-                    &text,
-                    ctx,
-                    guild_id,
-                    &new_message.mentions,
-                    &new_message.mention_roles,
-                    &new_message.mention_channels,
-                );
-                let text = text_preprocessor::preprocess(&text, 300);
-
-                let name = guild_id
-                    .to_guild_cached(ctx.cache.as_ref())
-                    .and_then(|guild| {
-                        guild
-                            .members
-                            .get(&new_message.author.id)
-                            .map(|member| member.display_name().to_string())
-                    })
-                    .unwrap_or_else(|| new_message.author.display_name().to_string());
+                let voice = resolve_voice(data, new_message.author.id, guild_id).await?;
+                let text = speech_text(ctx, data, guild_id, &session, &voice, new_message).await?;
+
+                let name =
+                    text_preprocessor::member_display_name(ctx, guild_id, &new_message.author);
+                let name = data.tts_locales.resolve(
+                    voice.language(),
+                    "speaker-name",
+                    None,
+                    Some(&fluent_args!["user" => name]),
+                )?;
 
                 if let Err(err) = session
                     .handle
-                    .speak(text, voice, Speaker::new(new_message.author.id, name))
+                    .speak(
+                        text,
+                        voice,
+                        Speaker::new(new_message.author.id, name),
+                        new_message.id,
+                    )
                     .await
                     .context("failed to send message")
                 {
                     tracing::error!("Error sending message: {:?}", err);
                     // lazy delete
-                    data.session_manager.remove(guild_id);
+                    data.session_manager.remove(guild_id).await;
+                }
+            }
+        }
+
+        serenity::FullEvent::MessageUpdate { new, event, .. } => {
+            // only content edits need to be read again
+            if event.content.is_none() {
+                return Ok(());
+            }
+
+            let Some(session) = find_reading_session(ctx, data, event.guild_id, event.channel_id)
+            else {
+                return Ok(());
+            };
+
+            let mut message = new.clone().unwrap_or_default();
+            event.apply_to_message(&mut message);
+            if message.author.bot {
+                return Ok(());
+            }
+
+            let guild_id = message
+                .guild_id
+                .ok_or(anyhow::anyhow!("Message does not contain guild ID"))?;
+
+            let voice = resolve_voice(data, message.author.id, guild_id).await?;
+            let text = speech_text(ctx, data, guild_id, &session, &voice, &message).await?;
+
+            session.handle.replace(message.id, text).await?;
+        }
+
+        serenity::FullEvent::MessageDelete {
+            channel_id,
+            deleted_message_id,
+            guild_id,
+        } => {
+            if let Some(session) = find_reading_session(ctx, data, *guild_id, *channel_id) {
+                session.handle.cancel(*deleted_message_id).await?;
+            }
+        }
+
+        serenity::FullEvent::MessageDeleteBulk {
+            channel_id,
+            multiple_deleted_messages_ids,
+            guild_id,
+        } => {
+            if let Some(session) = find_reading_session(ctx, data, *guild_id, *channel_id) {
+                for message_id in multiple_deleted_messages_ids {
+                    session.handle.cancel(*message_id).await?;
                 }
             }
         }
@@ -232,13 +269,223 @@ pub async fn event_handler(
     Ok(())
 }
 
+/// Voice chosen by the user, falling back to the guild and global defaults.
+async fn resolve_voice(
+    data: &Data,
+    user_id: serenity::UserId,
+    guild_id: serenity::GuildId,
+) -> anyhow::Result<Arc<dyn Voice>> {
+    let profile = data.resolver.resolve_with_fallback(user_id, guild_id).await;
+
+    let profile_str = match &profile {
+        Ok(profile) => profile.id.as_str(),
+        Err(_) => data.resolver.fallback(),
+    };
+
+    data.registry
+        .get_voice(profile_str)
+        .ok_or_else(|| anyhow::anyhow!("No voice preset found"))
+}
+
+/// Text to read for the message.
+async fn speech_text(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    session: &SessionInfo,
+    voice: &Arc<dyn Voice>,
+    message: &serenity::Message,
+) -> anyhow::Result<String> {
+    let text = text_preprocessor::normalize_mentions(
+        &message.content,
+        ctx,
+        guild_id,
+        &message.mentions,
+        &message.mention_roles,
+        &message.mention_channels,
+    );
+    let text = text_preprocessor::preprocess(&text, 300);
+
+    let settings = data.settings_repository.find(guild_id).await?;
+    let descriptions = description::describe(
+        message,
+        &settings.descriptions,
+        &data.tts_locales,
+        voice.language(),
+    )?;
+    let text = std::iter::once(text)
+        .filter(|text| !text.is_empty())
+        .chain(descriptions)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // say who it replies to, since replies lose context when heard
+    let text = match &message.referenced_message {
+        Some(referenced) if settings.announce_reply => {
+            let user = text_preprocessor::member_display_name(ctx, guild_id, &referenced.author);
+            data.tts_locales.resolve(
+                voice.language(),
+                "message-reply",
+                None,
+                Some(&fluent_args!["user" => user, "text" => text]),
+            )?
+        }
+        _ => text,
+    };
+
+    // say where it came from, when read from a channel added with add-channel
+    if session.extra_text_channels.contains(&message.channel_id)
+        && settings.announce_channel_name
+        && let Some(channel_name) = channel_name(ctx, guild_id, message.channel_id)
+    {
+        return data.tts_locales.resolve(
+            voice.language(),
+            "message-in-channel",
+            None,
+            Some(&fluent_args!["channel" => channel_name, "text" => text]),
+        );
+    }
+
+    Ok(text)
+}
+
+/// Finds the session reading the channel, either directly or as the parent of a thread.
+fn find_reading_session(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: Option<serenity::GuildId>,
+    channel_id: ChannelId,
+) -> Option<SessionInfo> {
+    if let Some(session) = data.session_manager.get_by_reading_channel(channel_id) {
+        return Some(session);
+    }
+
+    let parent_id = {
+        let guild = guild_id?.to_guild_cached(&ctx.cache)?;
+        guild
+            .threads
+            .iter()
+            .find(|thread| thread.id == channel_id)?
+            .parent_id?
+    };
+
+    data.session_manager.get_by_reading_channel(parent_id)
+}
+
+/// Name of the channel or thread, if cached.
+fn channel_name(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    channel_id: ChannelId,
+) -> Option<String> {
+    let guild = guild_id.to_guild_cached(&ctx.cache)?;
+    guild
+        .channels
+        .get(&channel_id)
+        .or_else(|| guild.threads.iter().find(|thread| thread.id == channel_id))
+        .map(|channel| channel.name.clone())
+}
+
+/// Rejoins sessions persisted before restart, if humans are still in the voice channel.
+async fn restore_sessions(ctx: &serenity::Context, data: &Data) -> Result<(), anyhow::Error> {
+    let persisted = data.session_manager.repository().find_all().await?;
+
+    for session in persisted {
+        if data.session_manager.get(session.guild).is_some() {
+            continue;
+        }
+
+        let has_humans = has_humans_in_channel(ctx, session.guild, session.voice, None)
+            .await
+            .unwrap_or(false);
+
+        if !has_humans {
+            tracing::info!(
+                "Discarding persisted session for guild {}: no one is in the voice channel",
+                session.guild
+            );
+            data.session_manager
+                .repository()
+                .delete(session.guild)
+                .await?;
+            continue;
+        }
+
+        tracing::info!("Restoring session for guild {}", session.guild);
+        let restored = async {
+            usecase::session::start(
+                ctx,
+                data,
+                session.guild,
+                session.text,
+                session.voice,
+                session.mode,
+            )
+            .await?;
+            for &channel in &session.extra_text {
+                data.session_manager
+                    .add_text_channel(session.guild, channel)
+                    .await?;
+            }
+            if session.followed.is_some() {
+                data.session_manager
+                    .set_followed(session.guild, session.followed)
+                    .await?;
+            }
+            anyhow::Ok(())
+        };
+        if let Err(err) = restored.await {
+            tracing::error!(
+                "Failed to restore session for guild {}: {:?}",
+                session.guild,
+                err
+            );
+            data.session_manager
+                .repository()
+                .delete(session.guild)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a human, other than `excluded`, is in the voice channel.
+async fn has_humans_in_channel(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    channel_id: ChannelId,
+    excluded: Option<serenity::UserId>,
+) -> Result<bool, anyhow::Error> {
+    // stage audience members are counted as well, since they hear the bot just like speakers do
+    let human_check_tasks = ctx
+        .cache
+        .guild(guild_id)
+        .ok_or_else(|| anyhow!("guild not found"))?
+        .voice_states
+        .iter()
+        .filter(|&(&user_id, voice_state)| {
+            voice_state.channel_id == Some(channel_id) && Some(user_id) != excluded
+        })
+        .map(|(&user_id, _)| {
+            let ctx = ctx.clone();
+            async move { user_id.to_user(ctx).await.map(|u| !u.bot).unwrap_or(true) }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(join_all(human_check_tasks)
+        .await
+        .into_iter()
+        .any(|is_human| is_human))
+}
+
 async fn shutdown_session(
     ctx: &serenity::Context,
     guild_id: serenity::GuildId,
     session_manager: &SessionManager,
 ) -> Result<(), anyhow::Error> {
     // free resources related session
-    session_manager.remove(guild_id);
+    session_manager.remove(guild_id).await;
     let manager = songbird::get(ctx)
         .await
         .ok_or_else(|| anyhow::anyhow!("Songbird Voice client not initialized"))?
@@ -282,7 +529,189 @@ fn voice_state_update_kind(old: &Option<VoiceState>, new: &VoiceState) -> Channe
 
 struct Notification {
     channel_id: serenity::ChannelId,
-    locale_id: &'static str,
+    kind: NotificationKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotificationKind {
+    Join,
+    Leave,
+}
+
+impl NotificationKind {
+    fn locale_id(self) -> &'static str {
+        match self {
+            NotificationKind::Join => "user-join",
+            NotificationKind::Leave => "user-leave",
+        }
+    }
+}
+
+/// Leave notifications held back by the debouncer, announced unless the user comes back in time.
+struct DeferredLeaves {
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+    after: Duration,
+    leaves: Vec<DeferredLeave>,
+}
+
+struct DeferredLeave {
+    channel_id: ChannelId,
+    token: u64,
+    /// prepared when leaving, `None` if no session reads the channel
+    announcement: Option<Announcement>,
+}
+
+impl DeferredLeaves {
+    async fn settle(self, debouncer: Arc<LeaveDebouncer>) {
+        tokio::time::sleep(self.after).await;
+
+        for leave in self.leaves {
+            if !debouncer.settle(self.guild_id, self.user_id, leave.channel_id, leave.token) {
+                continue;
+            }
+            if let Some(announcement) = leave.announcement
+                && let Err(e) = announcement.send().await
+            {
+                tracing::warn!("Failed to announce leave: {}", e);
+            }
+        }
+    }
+}
+
+/// Notification ready to be read in the session of its channel.
+struct Announcement {
+    handle: SessionHandle,
+    text: String,
+    voice: Arc<dyn Voice>,
+}
+
+impl Announcement {
+    async fn send(self) -> anyhow::Result<()> {
+        self.handle.announce(self.text, self.voice).await
+    }
+}
+
+/// Announces joins and leaves of the user according to the guild settings.
+///
+/// Leaves are returned instead of announced while debouncing is enabled.
+async fn announce_voice_transition(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    old: &Option<VoiceState>,
+    new: &VoiceState,
+) -> anyhow::Result<Option<DeferredLeaves>> {
+    let settings = data
+        .settings_repository
+        .find(guild_id)
+        .await?
+        .voice_notifications;
+    let (bot, roles) = member_attributes(ctx, guild_id, new);
+    if !settings.announces(bot, &roles) {
+        return Ok(None);
+    }
+
+    let user_id = new.user_id;
+    let debounce = settings.debounce();
+    let mut leaves = Vec::new();
+    for notification in generate_notifications(old, new) {
+        match (notification.kind, debounce) {
+            (NotificationKind::Leave, Some(_)) => {
+                let announcement =
+                    prepare_notification(ctx, data, guild_id, user_id, &notification).await?;
+                let token = data
+                    .leave_debouncer
+                    .leave(guild_id, user_id, notification.channel_id);
+                leaves.push(DeferredLeave {
+                    channel_id: notification.channel_id,
+                    token,
+                    announcement,
+                });
+                continue;
+            }
+            // came back before the leave was announced, so neither is announced
+            (NotificationKind::Join, _)
+                if data
+                    .leave_debouncer
+                    .join(guild_id, user_id, notification.channel_id) =>
+            {
+                continue;
+            }
+            _ => {}
+        }
+        send_notification(ctx, data, guild_id, user_id, &notification).await?;
+    }
+
+    Ok(debounce
+        .filter(|_| !leaves.is_empty())
+        .map(|after| DeferredLeaves {
+            guild_id,
+            user_id,
+            after,
+            leaves,
+        }))
+}
+
+/// Whether the member is a bot, and the roles of the member.
+fn member_attributes(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    state: &VoiceState,
+) -> (bool, Vec<serenity::RoleId>) {
+    if let Some(member) = &state.member {
+        return (member.user.bot, member.roles.clone());
+    }
+
+    ctx.cache
+        .guild(guild_id)
+        .and_then(|guild| {
+            guild
+                .members
+                .get(&state.user_id)
+                .map(|member| (member.user.bot, member.roles.clone()))
+        })
+        .unwrap_or_default()
+}
+
+async fn send_notification(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+    notification: &Notification,
+) -> anyhow::Result<()> {
+    if let Some(announcement) =
+        prepare_notification(ctx, data, guild_id, user_id, notification).await?
+    {
+        announcement.send().await?;
+    }
+
+    Ok(())
+}
+
+/// Builds the announcement of the notification, or `None` if no session reads its channel.
+async fn prepare_notification(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+    notification: &Notification,
+) -> anyhow::Result<Option<Announcement>> {
+    let Some(session) = data
+        .session_manager
+        .get_by_voice_channel(notification.channel_id)
+    else {
+        return Ok(None);
+    };
+
+    let (text, voice) =
+        session_notification(ctx, data, guild_id, user_id, notification.kind.locale_id()).await?;
+    Ok(Some(Announcement {
+        handle: session.handle,
+        text,
+        voice,
+    }))
 }
 
 fn generate_notifications(old: &Option<VoiceState>, new: &VoiceState) -> Vec<Notification> {
@@ -292,13 +721,13 @@ fn generate_notifications(old: &Option<VoiceState>, new: &VoiceState) -> Vec<Not
         ChannelTransition::Connect { new_channel_id } => {
             notifications.push(Notification {
                 channel_id: new_channel_id,
-                locale_id: "user-join",
+                kind: NotificationKind::Join,
             });
         }
         ChannelTransition::Disconnect { old_channel_id } => {
             notifications.push(Notification {
                 channel_id: old_channel_id,
-                locale_id: "user-leave",
+                kind: NotificationKind::Leave,
             });
         }
         ChannelTransition::Move {
@@ -307,11 +736,11 @@ fn generate_notifications(old: &Option<VoiceState>, new: &VoiceState) -> Vec<Not
         } => {
             notifications.push(Notification {
                 channel_id: old_channel_id,
-                locale_id: "user-leave",
+                kind: NotificationKind::Leave,
             });
             notifications.push(Notification {
                 channel_id: new_channel_id,
-                locale_id: "user-join",
+                kind: NotificationKind::Join,
             });
         }
         ChannelTransition::Ignore => {}
@@ -319,14 +748,14 @@ fn generate_notifications(old: &Option<VoiceState>, new: &VoiceState) -> Vec<Not
     notifications
 }
 
-async fn send_session_notification(
+/// Text of the notification about the user, and the voice to read it with.
+async fn session_notification(
     ctx: &serenity::Context,
     data: &Data,
-    handle: &SessionHandle,
     guild_id: serenity::GuildId,
     user_id: serenity::UserId,
     locale_id: &str,
-) -> Result<(), anyhow::Error> {
+) -> anyhow::Result<(String, Arc<dyn Voice>)> {
     let profile = data.resolver.resolve_with_fallback(user_id, guild_id).await;
 
     let profile_str = match &profile {
@@ -352,17 +781,12 @@ async fn send_session_notification(
                 .unwrap_or("someone".to_owned())
         });
 
-    handle
-        .announce(
-            data.tts_locales.resolve(
-                voice.language(),
-                locale_id,
-                None,
-                Some(&fluent_args!["user" => name]),
-            )?,
-            voice,
-        )
-        .await?;
+    let text = data.tts_locales.resolve(
+        voice.language(),
+        locale_id,
+        None,
+        Some(&fluent_args!["user" => name]),
+    )?;
 
-    Ok(())
+    Ok((text, voice))
 }
@@ -1,10 +1,13 @@
 pub mod binding;
 pub mod command;
 pub mod config;
+mod description;
 pub mod handler;
 pub mod localization;
 pub mod profile;
 pub mod session;
+pub mod settings;
 mod text_preprocessor;
 pub mod tts;
 pub mod usecase;
+pub mod voice_notification;
@@ -19,9 +19,14 @@ use text_to_speech_rs::config::{AppConfig, DatabaseConfig, DatabaseKind, load_co
 use text_to_speech_rs::handler::event_handler;
 use text_to_speech_rs::localization::{load_discord_locales, load_tts_locales};
 use text_to_speech_rs::profile::resolver::ProfileResolver;
+use text_to_speech_rs::session::event::EventBus;
 use text_to_speech_rs::session::manager::SessionManager;
+use text_to_speech_rs::session::repository::SessionRepository;
+use text_to_speech_rs::session::webhook::WebhookSink;
+use text_to_speech_rs::settings::GuildSettingsRepository;
 use text_to_speech_rs::tts::registry::VoicePackageRegistry;
-use text_to_speech_rs::tts::voicevox;
+use text_to_speech_rs::tts::{openai, voicevox};
+use text_to_speech_rs::voice_notification::LeaveDebouncer;
 use text_to_speech_rs::{command, handler};
 use tracing::{error, info};
 use tracing_subscriber::EnvFilter;
@@ -100,7 +105,7 @@ async fn cli_run(
     }
 
     let tts_locales = load_tts_locales("en")?;
-    let discord_locales = load_discord_locales("en-US")?;
+    let discord_locales = Arc::new(load_discord_locales("en-US")?);
 
     info!("Starting text-to-speech bot");
 
@@ -113,6 +118,7 @@ async fn cli_run(
     if config
         .backend
         .google_cloud
+        .as_ref()
         .is_some_and(|config| config.enabled)
     {
         info!("Using Google Cloud credentials");
@@ -121,10 +127,8 @@ async fn cli_run(
         registry_builder = registry_builder.google_cloud(client);
     }
 
-    if let Some(c) = config.backend.voicevox
-        && c.enabled
-    {
-        info!("Using Voicevox");
+    for (name, c) in config.backend.voicevox_engines()? {
+        info!("Using Voicevox engine '{}' at {}", name, c.url);
         let client = voicevox::Client::new(
             reqwest::ClientBuilder::new()
                 .timeout(Duration::from_secs(c.timeout))
@@ -132,7 +136,42 @@ async fn cli_run(
             Url::parse(&c.url)?,
         );
 
-        registry_builder = registry_builder.voicevox(client);
+        if c.generate_profiles {
+            match client.speakers().await {
+                Ok(speakers) => {
+                    if c.initialize_speakers {
+                        tokio::spawn(initialize_speakers(
+                            name.clone(),
+                            client.clone(),
+                            speakers.clone(),
+                        ));
+                    }
+                    registry_builder = registry_builder.voicevox_speakers(name.clone(), speakers);
+                }
+                // hand-written profiles keep working while the engine is down
+                Err(err) => error!("Failed to list speakers of '{}': {:?}", name, err),
+            }
+        }
+
+        registry_builder = registry_builder.voicevox(name, client);
+    }
+
+    for (name, c) in config
+        .backend
+        .openai_compatible
+        .iter()
+        .filter(|(_, c)| c.enabled)
+    {
+        info!("Using OpenAI-compatible engine '{}' at {}", name, c.url);
+        let client = openai::Client::new(
+            reqwest::ClientBuilder::new()
+                .timeout(Duration::from_secs(c.timeout))
+                .build()?,
+            &c.url,
+            c.api_key.clone(),
+        );
+
+        registry_builder = registry_builder.openai_compatible(name, client);
     }
 
     let registry = registry_builder
@@ -143,9 +182,12 @@ async fn cli_run(
 
     let data_db_path = data_dir.join("data.redb");
     let data_db = Arc::new(Database::create(data_db_path)?);
-    let binding_repository = BindingRepository::new(data_db);
+    let binding_repository = BindingRepository::new(data_db.clone());
     info!("Loaded bindings");
 
+    let settings_repository = GuildSettingsRepository::new(data_db.clone());
+    let session_repository = SessionRepository::new(data_db);
+
     let profile_repository = pool.profile_repository();
 
     let resolver = ProfileResolver::new(
@@ -153,6 +195,20 @@ async fn cli_run(
         config.bot.global_profile.clone(),
     );
 
+    let session_config = config.session.clone();
+
+    let events = EventBus::new();
+    if let Some(c) = &config.webhook {
+        info!("Posting session events to webhook");
+        let sink = WebhookSink::new(
+            reqwest::ClientBuilder::new()
+                .timeout(Duration::from_secs(c.timeout))
+                .build()?,
+            Url::parse(&c.url)?,
+        );
+        tokio::spawn(sink.run(events.subscribe()));
+    }
+
     let mut commands = command::commands();
 
     discord_locales.apply(&mut commands)?;
@@ -168,13 +224,17 @@ async fn cli_run(
         .setup(move |_ctx, _ready, _framework| {
             Box::pin(async move {
                 Ok(handler::Data {
-                    session_manager: SessionManager::new(),
+                    session_manager: SessionManager::new(session_repository),
                     registry,
                     resolver,
                     repository: profile_repository,
                     tts_locales,
                     discord_locales,
                     binding_repository,
+                    settings_repository,
+                    session_config,
+                    events,
+                    leave_debouncer: Arc::new(LeaveDebouncer::new()),
                 })
             })
         })
@@ -217,3 +277,26 @@ async fn prepare_database(config: &DatabaseConfig) -> anyhow::Result<WrappedPool
         }
     }
 }
+
+/// Warms every talk style of the engine one by one, so that the first message is not slow.
+async fn initialize_speakers(
+    name: String,
+    client: voicevox::Client,
+    speakers: Vec<voicevox::Speaker>,
+) {
+    let styles = speakers
+        .iter()
+        .flat_map(|speaker| speaker.styles.iter())
+        .filter(|style| style.is_talk());
+
+    for style in styles {
+        if let Err(err) = client.initialize_speaker(style.id).await {
+            error!(
+                "Failed to initialize style {} of '{}': {:?}",
+                style.id, name, err
+            );
+        }
+    }
+
+    info!("Initialized speakers of '{}'", name);
+}
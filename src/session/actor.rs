@@ -1,37 +1,103 @@
+use crate::config::SessionConfig;
 use crate::session::driver::AudioDriver;
-use crate::session::{Priority, SessionCommand, SessionHandle, Speaker};
-use crate::tts::Voice;
-use poise::serenity_prelude::UserId;
+use crate::session::event::{EndReason, SessionEventKind, SessionEvents};
+use crate::session::notifier::{Notice, Notifier};
+use crate::session::queue::ReadingQueue;
+use crate::session::{
+    NameAnnouncement, Priority, SessionCommand, SessionHandle, Speaker, SpeakerNamePolicy,
+};
+use crate::tts::{Voice, scheduler};
+use anyhow::Context;
+use poise::serenity_prelude::futures::future::try_join_all;
+use poise::serenity_prelude::{GuildId, MessageId, UserId};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::select;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
 use tracing;
 
-#[derive(Clone)]
+/// Minimum interval between notices about dropped messages.
+const DROP_NOTICE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Upper bound of the delay between attempts to rejoin the voice channel.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// How long to wait for the farewell announcement to be played before leaving anyway.
+const FAREWELL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Leaves the voice channel when nothing has been spoken for a while.
+pub struct IdleTimeout {
+    /// disabled if `None`
+    pub after: Option<Duration>,
+    /// read before leaving
+    pub announcement: String,
+    pub voice: Arc<dyn Voice>,
+}
+
 enum WorkerCommand {
     GenerateAndPlay(GenerateAndPlay),
+    Skip,
+    Flush,
+    Pause,
+    Resume,
+    SetNameAnnouncement(NameAnnouncement),
+    Cancel(MessageId),
+    Replace {
+        message_id: MessageId,
+        text: String,
+    },
+    /// replies once everything enqueued so far has been played
+    WaitIdle(oneshot::Sender<()>),
 }
 
-#[derive(Clone)]
 struct GenerateAndPlay {
     text: String,
     speaker: Option<Speaker>,
+    message_id: Option<MessageId>,
     voice: Arc<dyn Voice>,
 }
 
 pub struct SessionActor {
     rx: mpsc::Receiver<SessionCommand>,
     system_tx: mpsc::Sender<WorkerCommand>,
-    user_tx: broadcast::Sender<WorkerCommand>,
+    user_queue: Arc<ReadingQueue<GenerateAndPlay>>,
     driver: Arc<dyn AudioDriver>,
+    notifier: Arc<dyn Notifier>,
+    dropped: usize,
+    last_drop_notice: Option<tokio::time::Instant>,
+    /// when the drops throttled since the last notice are to be reported
+    drop_notice_due: Option<tokio::time::Instant>,
+    idle_timeout: IdleTimeout,
+    /// when the worker ran out of things to play, or `None` while busy
+    idle_since: watch::Receiver<Option<tokio::time::Instant>>,
+    /// when the idle timer was restarted by a command
+    idle_restarted: tokio::time::Instant,
+    reconnect_attempts: usize,
+    reconnect_delay: Duration,
+    /// paused by command, kept to restore after reconnecting
+    paused: bool,
+    /// waiting to rejoin the voice channel, with the worker paused
+    reconnecting: bool,
+    worker: JoinHandle<()>,
+    events: SessionEvents,
 }
 
 impl SessionActor {
-    pub fn new(driver: Arc<dyn AudioDriver>) -> (Self, SessionHandle) {
+    pub fn new(
+        driver: Arc<dyn AudioDriver>,
+        config: &SessionConfig,
+        name_announcement: NameAnnouncement,
+        idle_timeout: IdleTimeout,
+        notifier: Arc<dyn Notifier>,
+        events: SessionEvents,
+    ) -> (Self, SessionHandle) {
         let (cmd_tx, cmd_rx) = mpsc::channel(100);
 
         let (system_tx, system_rx) = mpsc::channel(100);
-        let (user_tx, user_rx) = broadcast::channel(100);
+        let user_queue = Arc::new(ReadingQueue::new(config.max_backlog, config.drop_policy));
+        let (idle_tx, idle_rx) = watch::channel(Some(tokio::time::Instant::now()));
 
         {
             let driver = driver.clone();
@@ -41,12 +107,34 @@ impl SessionActor {
             });
         }
 
-        tokio::spawn(Self::worker_loop(driver.clone(), system_rx, user_rx));
+        let worker = tokio::spawn(
+            Worker::new(
+                driver.clone(),
+                config.prefetch,
+                name_announcement,
+                idle_tx,
+                events.clone(),
+            )
+            .run(system_rx, user_queue.clone()),
+        );
         let actor = Self {
             rx: cmd_rx,
             system_tx,
-            user_tx,
+            user_queue,
             driver,
+            notifier,
+            dropped: 0,
+            last_drop_notice: None,
+            drop_notice_due: None,
+            idle_timeout,
+            idle_since: idle_rx,
+            idle_restarted: tokio::time::Instant::now(),
+            reconnect_attempts: config.reconnect_attempts,
+            reconnect_delay: Duration::from_millis(config.reconnect_delay_ms),
+            paused: false,
+            reconnecting: false,
+            worker,
+            events,
         };
 
         (actor, SessionHandle::new(cmd_tx))
@@ -55,168 +143,1169 @@ impl SessionActor {
     pub async fn run(mut self) {
         tracing::info!("Session actor started");
 
-        while let Some(cmd) = self.rx.recv().await {
-            match cmd {
-                SessionCommand::Speak {
-                    text,
-                    voice,
-                    speaker,
-                    priority,
-                } => {
-                    let command = WorkerCommand::GenerateAndPlay(GenerateAndPlay {
-                        text,
-                        speaker,
-                        voice,
-                    });
-
-                    // ignore since not recoverable
-                    match priority {
-                        Priority::System => {
-                            let _ = self.system_tx.send(command).await;
-                        }
-                        Priority::User => {
-                            let _ = self.user_tx.send(command);
-                        }
-                    };
-                }
-                SessionCommand::Stop => {}
-                SessionCommand::Leave => {
-                    tracing::info!("Received Leave command");
-                    break;
+        let reason = loop {
+            let idle_deadline = self.idle_deadline();
+            let cmd = select! {
+                cmd = self.rx.recv() => cmd,
+                Ok(()) = self.idle_since.changed() => continue,
+                _ = sleep_until(self.drop_notice_due) => {
+                    self.post_drop_notice().await;
+                    continue;
                 }
-                SessionCommand::Disconnect => {
-                    tracing::warn!("Driver disconnected unexpectedly");
-                    break;
+                _ = sleep_until(idle_deadline) => {
+                    self.leave_on_idle().await;
+                    break EndReason::Idle;
                 }
+            };
+
+            let Some(cmd) = cmd else {
+                break EndReason::Left;
+            };
+            if let Some(reason) = self.handle(cmd).await {
+                break reason;
             }
-        }
+        };
 
         tracing::info!("Session actor stopping, cleaning up...");
 
+        // stop the worker first, so that utterances cut off by leaving are not reported as spoken
+        drop(self.system_tx);
+        if let Err(e) = self.worker.await {
+            tracing::error!("Worker failed: {}", e);
+        }
+
         if let Err(e) = self.driver.leave().await {
             tracing::error!("Failed to leave voice channel during cleanup: {}", e);
         } else {
             tracing::info!("Successfully left voice channel.");
         }
+
+        self.events.emit(SessionEventKind::Ended { reason });
+    }
+
+    /// Handles a command, telling why the session has to end if it does.
+    async fn handle(&mut self, cmd: SessionCommand) -> Option<EndReason> {
+        match cmd {
+            SessionCommand::Speak {
+                text,
+                voice,
+                speaker,
+                message_id,
+                priority,
+            } => {
+                let command = GenerateAndPlay {
+                    text,
+                    speaker,
+                    message_id,
+                    voice,
+                };
+
+                match priority {
+                    Priority::System => {
+                        // ignore since not recoverable
+                        let _ = self
+                            .system_tx
+                            .send(WorkerCommand::GenerateAndPlay(command))
+                            .await;
+                    }
+                    Priority::User => {
+                        let dropped = self.user_queue.push(command);
+                        if dropped > 0 {
+                            self.report_dropped(dropped).await;
+                        }
+                    }
+                };
+            }
+            SessionCommand::Cancel(message_id) => {
+                let removed = self
+                    .user_queue
+                    .remove_if(|cmd| cmd.message_id == Some(message_id));
+                // it may have been taken by the worker already
+                if removed == 0 {
+                    let _ = self.system_tx.send(WorkerCommand::Cancel(message_id)).await;
+                }
+            }
+            SessionCommand::Replace { message_id, text } => {
+                let mut text = Some(text);
+                let replaced = self.user_queue.update_first(
+                    |cmd| cmd.message_id == Some(message_id),
+                    |cmd| cmd.text = text.take().expect("replaced only once"),
+                );
+                if !replaced && let Some(text) = text {
+                    let _ = self
+                        .system_tx
+                        .send(WorkerCommand::Replace { message_id, text })
+                        .await;
+                }
+            }
+            SessionCommand::Skip => {
+                let _ = self.system_tx.send(WorkerCommand::Skip).await;
+            }
+            SessionCommand::Stop => {
+                // flush in worker to order it after the generation in progress
+                let _ = self.system_tx.send(WorkerCommand::Flush).await;
+            }
+            SessionCommand::Pause => {
+                self.paused = true;
+                let _ = self.system_tx.send(WorkerCommand::Pause).await;
+            }
+            SessionCommand::Resume => {
+                self.paused = false;
+                self.idle_restarted = tokio::time::Instant::now();
+                // resumed once reconnected
+                if !self.reconnecting {
+                    let _ = self.system_tx.send(WorkerCommand::Resume).await;
+                }
+            }
+            SessionCommand::SetVolume(volume) => {
+                if let Err(e) = self.driver.set_volume(volume).await {
+                    tracing::warn!("Failed to set volume: {}", e);
+                }
+            }
+            SessionCommand::SetNameAnnouncement(announcement) => {
+                let _ = self
+                    .system_tx
+                    .send(WorkerCommand::SetNameAnnouncement(announcement))
+                    .await;
+            }
+            SessionCommand::SetIdleTimeout(after) => {
+                self.idle_timeout.after = after;
+                self.idle_restarted = tokio::time::Instant::now();
+            }
+            SessionCommand::Leave => {
+                tracing::info!("Received Leave command");
+                return Some(EndReason::Left);
+            }
+            SessionCommand::Disconnect { recoverable } => {
+                tracing::warn!("Driver disconnected unexpectedly");
+                if self.reconnecting {
+                    return None;
+                }
+                if !recoverable {
+                    return Some(EndReason::Disconnected);
+                }
+                return self.reconnect().await;
+            }
+        }
+
+        None
+    }
+
+    /// Tries to rejoin the voice channel with backoff, holding messages back meanwhile.
+    ///
+    /// Commands are still handled while waiting. A notice is posted if every attempt fails.
+    async fn reconnect(&mut self) -> Option<EndReason> {
+        self.reconnecting = true;
+        let _ = self.system_tx.send(WorkerCommand::Pause).await;
+
+        let result = self.try_reconnect().await;
+
+        self.reconnecting = false;
+        match result {
+            Ok(true) => {
+                if !self.paused {
+                    let _ = self.system_tx.send(WorkerCommand::Resume).await;
+                }
+                None
+            }
+            Ok(false) => {
+                self.notifier
+                    .notify(Notice::ReconnectFailed {
+                        attempts: self.reconnect_attempts,
+                    })
+                    .await;
+                Some(EndReason::ReconnectFailed)
+            }
+            Err(reason) => Some(reason),
+        }
+    }
+
+    /// Returns whether it has been reconnected, or why the session ended meanwhile.
+    async fn try_reconnect(&mut self) -> Result<bool, EndReason> {
+        let driver = self.driver.clone();
+        let mut delay = self.reconnect_delay;
+        for attempt in 1..=self.reconnect_attempts {
+            self.handling_commands(tokio::time::sleep(delay)).await?;
+
+            match self.handling_commands(driver.reconnect()).await? {
+                Ok(()) => {
+                    tracing::info!("Reconnected on attempt {}", attempt);
+                    return Ok(true);
+                }
+                Err(e) => {
+                    tracing::warn!("Reconnect attempt {} failed: {}", attempt, e);
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Waits for `f` while handling commands, or returns why the session ended meanwhile.
+    async fn handling_commands<F: Future>(&mut self, f: F) -> Result<F::Output, EndReason> {
+        tokio::pin!(f);
+        loop {
+            select! {
+                output = &mut f => return Ok(output),
+                cmd = self.rx.recv() => {
+                    let Some(cmd) = cmd else {
+                        return Err(EndReason::Left);
+                    };
+                    if let Some(reason) = Box::pin(self.handle(cmd)).await {
+                        return Err(reason);
+                    }
+                }
+            }
+        }
+    }
+
+    /// When to leave for inactivity, unless paused or messages are waiting to be read.
+    fn idle_deadline(&self) -> Option<tokio::time::Instant> {
+        if self.paused || !self.user_queue.is_empty() {
+            return None;
+        }
+        let idle_since = (*self.idle_since.borrow())?;
+        let after = self.idle_timeout.after?;
+        Some(idle_since.max(self.idle_restarted) + after)
     }
 
-    async fn worker_loop(
+    /// Reads the farewell announcement, waiting until it has been played.
+    async fn leave_on_idle(&mut self) {
+        tracing::info!("Leaving due to inactivity");
+
+        let command = GenerateAndPlay {
+            text: self.idle_timeout.announcement.clone(),
+            speaker: None,
+            message_id: None,
+            voice: self.idle_timeout.voice.clone(),
+        };
+        let (done_tx, done_rx) = oneshot::channel();
+        let _ = self
+            .system_tx
+            .send(WorkerCommand::GenerateAndPlay(command))
+            .await;
+        let _ = self.system_tx.send(WorkerCommand::WaitIdle(done_tx)).await;
+
+        if tokio::time::timeout(FAREWELL_TIMEOUT, done_rx)
+            .await
+            .is_err()
+        {
+            tracing::warn!("Farewell announcement was not played in time");
+        }
+    }
+
+    async fn report_dropped(&mut self, count: usize) {
+        tracing::warn!("reading queue overflowed, dropped {} messages", count);
+        self.events.emit(SessionEventKind::MessageDropped { count });
+        self.dropped += count;
+
+        if let Some(last) = self.last_drop_notice
+            && last.elapsed() < DROP_NOTICE_INTERVAL
+        {
+            // report them together once the interval has passed
+            self.drop_notice_due = Some(last + DROP_NOTICE_INTERVAL);
+            return;
+        }
+
+        self.post_drop_notice().await;
+    }
+
+    async fn post_drop_notice(&mut self) {
+        self.notifier
+            .notify(Notice::MessagesDropped {
+                count: self.dropped,
+            })
+            .await;
+        self.dropped = 0;
+        self.last_drop_notice = Some(tokio::time::Instant::now());
+        self.drop_notice_due = None;
+    }
+}
+
+/// Sleeps until the deadline, or forever if there is none.
+async fn sleep_until(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+type Synthesis = JoinHandle<anyhow::Result<Vec<Vec<u8>>>>;
+
+/// Utterance enqueued to the driver and not finished yet.
+struct Playing {
+    message_id: Option<MessageId>,
+    /// read for a user message, as opposed to a system one
+    user: bool,
+    /// tracks not finished yet
+    remaining: usize,
+}
+
+/// Message being synthesized, kept until it is enqueued to the driver.
+struct Pending {
+    message_id: Option<MessageId>,
+    /// name segment read before the text, if any
+    name: Option<String>,
+    voice: Arc<dyn Voice>,
+    synthesis: Synthesis,
+}
+
+/// Synthesizes upcoming messages concurrently and plays them in order.
+///
+/// At most `prefetch` user messages are held at once, counting both the ones
+/// being synthesized and the ones enqueued to the driver but not finished yet.
+/// System messages are not counted, so that they never hold user messages back.
+struct Worker {
+    driver: Arc<dyn AudioDriver>,
+    prefetch: usize,
+    system_pipeline: VecDeque<Pending>,
+    user_pipeline: VecDeque<Pending>,
+    /// utterances enqueued to the driver, in playing order
+    playing: VecDeque<Playing>,
+    last_speaker_id: Option<UserId>,
+    name_announcement: NameAnnouncement,
+    /// when the last utterance finished playing
+    last_finished: Option<Instant>,
+    /// synthesized audios are held back while paused
+    paused: bool,
+    /// notified once nothing is left to play
+    idle_waiters: Vec<oneshot::Sender<()>>,
+    /// tells since when nothing has been left to play, including the backlog
+    idle_tx: watch::Sender<Option<tokio::time::Instant>>,
+    events: SessionEvents,
+}
+
+impl Worker {
+    fn new(
         driver: Arc<dyn AudioDriver>,
+        prefetch: usize,
+        name_announcement: NameAnnouncement,
+        idle_tx: watch::Sender<Option<tokio::time::Instant>>,
+        events: SessionEvents,
+    ) -> Self {
+        Self {
+            driver,
+            prefetch: prefetch.max(1),
+            system_pipeline: VecDeque::new(),
+            user_pipeline: VecDeque::new(),
+            playing: VecDeque::new(),
+            last_speaker_id: None,
+            name_announcement,
+            last_finished: None,
+            paused: false,
+            idle_waiters: Vec::new(),
+            idle_tx,
+            events,
+        }
+    }
+
+    async fn run(
+        mut self,
         mut system_rx: mpsc::Receiver<WorkerCommand>,
-        mut user_rx: broadcast::Receiver<WorkerCommand>,
+        user_queue: Arc<ReadingQueue<GenerateAndPlay>>,
     ) {
         tracing::info!("Worker started");
 
-        // Token system for eager voice generation
-        // user voice generation is throttled with tokens
-        const INITIAL_TOKEN: usize = 3;
-        let mut tokens: isize = INITIAL_TOKEN as isize;
-
-        let mut songbird_rx = {
-            let (tx, rx) = mpsc::channel(INITIAL_TOKEN * 2);
-            driver.subscribe_to_end_event(tx).await;
+        let mut end_rx = {
+            let (tx, rx) = mpsc::channel(self.prefetch * 2);
+            self.driver.subscribe_to_end_event(tx).await;
             rx
         };
 
-        let mut last_speaker_id: Option<UserId> = None;
-
         loop {
-            let user_can_consume = tokens > 0;
+            let user_playing = self.playing.iter().filter(|playing| playing.user).count();
+            let can_prefetch = self.user_pipeline.len() + user_playing < self.prefetch;
 
             select! {
                 biased;
-                Some(_) = songbird_rx.recv() => {
-                    if tokens < INITIAL_TOKEN as isize {
-                        tokens += 1;
-                        tracing::debug!("Token released. Current: {}", tokens);
-                    }
+                Some(_) = end_rx.recv() => {
+                    self.on_track_end();
                 }
-                Some(cmd) = system_rx.recv() => {
+                cmd = system_rx.recv() => {
                     match cmd {
-                        WorkerCommand::GenerateAndPlay(cmd) => {
-                            let mut segments = Vec::new();
-                            let current_speaker = cmd.speaker.as_ref().map(|s| s.user_id);
-
-                            // read name when current speaker is not same as last one.
-                            if current_speaker != last_speaker_id && let Some(speaker) = cmd.speaker {
-                                last_speaker_id = current_speaker;
-                                segments.push(speaker.name);
-                            }
-                            segments.push(cmd.text.clone());
-
-                            match Self::generate_and_play(segments, cmd.voice, driver.clone()).await {
-
-                                Ok(len) => {
-                                    tracing::debug!("consuming {} tokens", len);
-                                    tokens -= len as isize;
-                                }
-                                Err(err) => {
-                                    tracing::warn!("Couldn't generate playback: {:?}", err);
-                                }
-                            }
-                        },
-                    }
-                }
-
-                cmd_result = user_rx.recv(), if user_can_consume => {
-                    match cmd_result {
-                        Ok(WorkerCommand::GenerateAndPlay(cmd)) => {
-                            let mut segments = Vec::new();
-                            let current_speaker = cmd.speaker.as_ref().map(|s| s.user_id);
-
-                            // read name when current speaker is not same as last one.
-                            if current_speaker != last_speaker_id && let Some(speaker) = cmd.speaker {
-                                last_speaker_id = current_speaker;
-                                segments.push(speaker.name);
-                            }
-                            segments.push(cmd.text.clone());
-
-                            match Self::generate_and_play(segments, cmd.voice, driver.clone()).await {
-                                Ok(len) => {
-                                    tracing::debug!("consuming {} tokens", len);
-                                    tokens -= len as isize;
-                                }
-                                Err(err) => {
-                                    tracing::warn!("Couldn't generate playback: {:?}", err);
-                                }
-                            }
-                        },
-                        Err(broadcast::error::RecvError::Lagged(count)) => {
-                            tracing::warn!("worker lagged, skip {} commands", count);
-                            continue;
-                        },
-                        Err(broadcast::error::RecvError::Closed) => {
+                        Some(WorkerCommand::GenerateAndPlay(cmd)) => {
+                            let pending = self.synthesize(cmd);
+                            self.system_pipeline.push_back(pending);
+                        }
+                        Some(WorkerCommand::Skip) => {
+                            self.skip().await;
+                        }
+                        Some(WorkerCommand::Flush) => {
+                            self.flush(&user_queue).await;
+                        }
+                        Some(WorkerCommand::Pause) => {
+                            self.set_paused(true).await;
+                        }
+                        Some(WorkerCommand::Resume) => {
+                            self.set_paused(false).await;
+                        }
+                        Some(WorkerCommand::SetNameAnnouncement(announcement)) => {
+                            self.name_announcement = announcement;
+                        }
+                        Some(WorkerCommand::Cancel(message_id)) => {
+                            self.cancel(message_id).await;
+                        }
+                        Some(WorkerCommand::Replace { message_id, text }) => {
+                            self.replace(message_id, text);
+                        }
+                        Some(WorkerCommand::WaitIdle(tx)) => {
+                            self.idle_waiters.push(tx);
+                        }
+                        None => {
                             tracing::info!("worker closed");
                             break;
                         }
                     }
                 }
-                else => {
-                    break;
+                (message_id, result) = next_synthesized(&mut self.system_pipeline), if !self.paused => {
+                    self.play(message_id, result, false).await;
+                }
+                (message_id, result) = next_synthesized(&mut self.user_pipeline), if !self.paused => {
+                    self.play(message_id, result, true).await;
+                }
+                cmd = user_queue.pop(), if can_prefetch => {
+                    let pending = self.synthesize(cmd);
+                    self.user_pipeline.push_back(pending);
                 }
             }
+
+            if !self.idle_waiters.is_empty() && self.is_idle() {
+                for tx in self.idle_waiters.drain(..) {
+                    let _ = tx.send(());
+                }
+            }
+
+            let idle = self.is_idle() && user_queue.is_empty();
+            self.idle_tx
+                .send_if_modified(|since| match (idle, since.is_some()) {
+                    (true, false) => {
+                        *since = Some(tokio::time::Instant::now());
+                        true
+                    }
+                    (false, true) => {
+                        *since = None;
+                        true
+                    }
+                    _ => false,
+                });
+        }
+
+        for pending in self.system_pipeline.iter().chain(self.user_pipeline.iter()) {
+            pending.synthesis.abort();
         }
     }
 
-    async fn generate_and_play(
-        segment: Vec<String>,
-        voice: Arc<dyn Voice>,
-        driver: Arc<dyn AudioDriver>,
-    ) -> anyhow::Result<usize> {
-        let mut audios = Vec::new();
-        for segment in segment.iter() {
-            let audio_data = match voice.generate(segment).await {
-                Ok(data) => data,
-                Err(e) => {
-                    return Err(anyhow::anyhow!(e).context("Failed to generate voice"));
+    /// Starts synthesizing segments of the message in background.
+    fn synthesize(&mut self, cmd: GenerateAndPlay) -> Pending {
+        let name = cmd.speaker.and_then(|speaker| {
+            let announce = self.should_announce(speaker.user_id);
+            self.last_speaker_id = Some(speaker.user_id);
+            announce.then_some(speaker.name)
+        });
+
+        Pending {
+            message_id: cmd.message_id,
+            synthesis: spawn_synthesis(
+                self.events.guild_id(),
+                cmd.voice.clone(),
+                name.clone(),
+                cmd.text,
+            ),
+            name,
+            voice: cmd.voice,
+        }
+    }
+
+    /// Drops the message, removing it from the driver if it has been enqueued already.
+    async fn cancel(&mut self, message_id: MessageId) {
+        if let Some(index) = self
+            .user_pipeline
+            .iter()
+            .position(|pending| pending.message_id == Some(message_id))
+            && let Some(pending) = self.user_pipeline.remove(index)
+        {
+            pending.synthesis.abort();
+            tracing::debug!("Cancelled message {} before playing", message_id);
+            return;
+        }
+
+        if let Some(index) = self
+            .playing
+            .iter()
+            .position(|playing| playing.message_id == Some(message_id))
+        {
+            match self.driver.remove(message_id).await {
+                Ok(()) => {
+                    self.playing.remove(index);
+                    tracing::debug!("Removed message {} from the driver", message_id);
                 }
-            };
-            audios.push(audio_data);
+                Err(e) => tracing::warn!("Failed to remove message {}: {}", message_id, e),
+            }
+        }
+    }
+
+    /// Synthesizes the new text instead, if the message has not been enqueued yet.
+    fn replace(&mut self, message_id: MessageId, text: String) {
+        if let Some(pending) = self
+            .user_pipeline
+            .iter_mut()
+            .find(|pending| pending.message_id == Some(message_id))
+        {
+            pending.synthesis.abort();
+            pending.synthesis = spawn_synthesis(
+                self.events.guild_id(),
+                pending.voice.clone(),
+                pending.name.clone(),
+                text,
+            );
+            tracing::debug!("Replaced message {} before playing", message_id);
+        }
+    }
+
+    fn should_announce(&self, speaker: UserId) -> bool {
+        let changed = self.last_speaker_id != Some(speaker);
+
+        match self.name_announcement.policy {
+            SpeakerNamePolicy::Always => true,
+            SpeakerNamePolicy::OnChange => changed,
+            SpeakerNamePolicy::OnChangeOrSilence => {
+                changed || self.silent_for(self.name_announcement.silence)
+            }
+            SpeakerNamePolicy::Never => false,
         }
+    }
+
+    /// Whether nothing has been played nor is about to be played for `duration`.
+    fn silent_for(&self, duration: Duration) -> bool {
+        self.playing.is_empty()
+            && self.user_pipeline.is_empty()
+            && self.system_pipeline.is_empty()
+            && self
+                .last_finished
+                .is_none_or(|finished| finished.elapsed() >= duration)
+    }
+
+    fn is_idle(&self) -> bool {
+        self.system_pipeline.is_empty() && self.user_pipeline.is_empty() && self.playing.is_empty()
+    }
+
+    async fn play(
+        &mut self,
+        message_id: Option<MessageId>,
+        result: anyhow::Result<Vec<Vec<u8>>>,
+        user: bool,
+    ) {
+        match result {
+            Ok(audios) if !audios.is_empty() => {
+                self.playing.push_back(Playing {
+                    message_id,
+                    user,
+                    remaining: audios.len(),
+                });
+                self.driver.enqueue(message_id, audios).await;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!("Couldn't generate playback: {:?}", err);
+                self.events.emit(SessionEventKind::SynthesisFailed {
+                    message_id,
+                    error: format!("{:#}", err),
+                });
+            }
+        }
+    }
+
+    fn on_track_end(&mut self) {
+        if let Some(playing) = self.playing.front_mut() {
+            playing.remaining = playing.remaining.saturating_sub(1);
+            if playing.remaining == 0 {
+                let message_id = playing.message_id;
+                self.playing.pop_front();
+                self.events
+                    .emit(SessionEventKind::MessageSpoken { message_id });
+                self.last_finished = Some(Instant::now());
+                tracing::debug!("Utterance finished. Playing: {}", self.playing.len());
+            }
+        }
+    }
+
+    /// Cuts off the utterance playing, which is not reported as spoken.
+    async fn skip(&mut self) {
+        match self.driver.skip().await {
+            Ok(true) => {
+                self.playing.pop_front();
+                self.last_finished = Some(Instant::now());
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to skip current utterance: {}", e),
+        }
+    }
+
+    async fn set_paused(&mut self, paused: bool) {
+        if self.paused == paused {
+            return;
+        }
+        self.paused = paused;
+
+        let result = if paused {
+            self.driver.pause().await
+        } else {
+            self.driver.resume().await
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to change pause state to {}: {}", paused, e);
+        }
+    }
+
+    async fn flush(&mut self, user_queue: &ReadingQueue<GenerateAndPlay>) {
+        for pending in self.user_pipeline.drain(..) {
+            pending.synthesis.abort();
+        }
+        let dropped = user_queue.clear();
+        tracing::info!("Flushed {} pending messages", dropped);
+
+        match self.driver.stop().await {
+            Ok(()) => self.playing.clear(),
+            Err(e) => tracing::warn!("Failed to stop playback: {}", e),
+        }
+    }
+}
+
+/// Starts synthesizing the name, if any, and the text in background.
+fn spawn_synthesis(
+    guild: GuildId,
+    voice: Arc<dyn Voice>,
+    name: Option<String>,
+    text: String,
+) -> Synthesis {
+    let segments: Vec<String> = name.into_iter().chain(std::iter::once(text)).collect();
+
+    tokio::spawn(scheduler::scope(guild, async move {
+        try_join_all(segments.iter().map(|segment| voice.generate(segment)))
+            .await
+            .context("Failed to generate voice")
+    }))
+}
+
+/// Waits for the oldest synthesis in the pipeline, or forever if it is empty.
+async fn next_synthesized(
+    pipeline: &mut VecDeque<Pending>,
+) -> (Option<MessageId>, anyhow::Result<Vec<Vec<u8>>>) {
+    let Some(pending) = pipeline.front_mut() else {
+        return std::future::pending().await;
+    };
+
+    let result = (&mut pending.synthesis).await;
+    let message_id = pending.message_id;
+    pipeline.pop_front();
+
+    let result = result
+        .context("Synthesis task failed")
+        .and_then(|result| result);
+    (message_id, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::event::{EventBus, SessionEvent};
+    use crate::session::queue::DropPolicy;
+    use crate::session::test_utils::{RecordingDriver, RecordingNotifier};
+    use crate::tts::test_utils::MockVoice;
+    use poise::serenity_prelude::GuildId;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::sync::broadcast;
+
+    struct Fixture {
+        handle: SessionHandle,
+        driver: Arc<RecordingDriver>,
+        notifier: Arc<RecordingNotifier>,
+        events: broadcast::Receiver<SessionEvent>,
+        voice: MockVoice,
+        next_message_id: AtomicU64,
+    }
+
+    fn start(config: SessionConfig, policy: SpeakerNamePolicy) -> Fixture {
+        start_with_idle_timeout(config, policy, None)
+    }
+
+    fn start_with_idle_timeout(
+        config: SessionConfig,
+        policy: SpeakerNamePolicy,
+        idle_after: Option<Duration>,
+    ) -> Fixture {
+        let driver = Arc::new(RecordingDriver::new());
+        let notifier = Arc::new(RecordingNotifier::new());
+        let voice = MockVoice::new();
+        let name_announcement = NameAnnouncement {
+            policy,
+            silence: Duration::from_secs(30),
+        };
+        let idle_timeout = IdleTimeout {
+            after: idle_after,
+            announcement: "idle".to_owned(),
+            voice: Arc::new(voice.clone()),
+        };
+        let bus = EventBus::new();
+        let events = bus.subscribe();
+        let (actor, handle) = SessionActor::new(
+            driver.clone(),
+            &config,
+            name_announcement,
+            idle_timeout,
+            notifier.clone(),
+            bus.for_guild(GuildId::new(1)),
+        );
+        tokio::spawn(actor.run());
+
+        Fixture {
+            handle,
+            driver,
+            notifier,
+            events,
+            voice,
+            next_message_id: AtomicU64::new(1),
+        }
+    }
+
+    impl Fixture {
+        async fn speak(&self, user: u64, text: &str) -> MessageId {
+            let message_id = MessageId::new(self.next_message_id.fetch_add(1, Ordering::SeqCst));
+            let speaker = Speaker::new(UserId::new(user), format!("user{}", user));
+            self.handle
+                .speak(
+                    text.to_owned(),
+                    Arc::new(self.voice.clone()),
+                    speaker,
+                    message_id,
+                )
+                .await
+                .unwrap();
+            message_id
+        }
+
+        async fn announce(&self, text: &str) {
+            self.handle
+                .announce(text.to_owned(), Arc::new(self.voice.clone()))
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Lets spawned tasks run until they are blocked.
+    async fn settle() {
+        for _ in 0..20 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn name_is_read_when_speaker_changes() {
+        let fixture = start(SessionConfig::default(), SpeakerNamePolicy::OnChange);
+
+        fixture.speak(1, "hello").await;
+        fixture.speak(1, "again").await;
+        fixture.speak(2, "hi").await;
+        fixture.driver.wait_for_utterances(3).await;
+
+        assert_eq!(
+            fixture.driver.texts(),
+            vec![
+                vec!["user1".to_owned(), "hello".to_owned()],
+                vec!["again".to_owned()],
+                vec!["user2".to_owned(), "hi".to_owned()],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn name_is_never_read_when_disabled() {
+        let fixture = start(SessionConfig::default(), SpeakerNamePolicy::Never);
+
+        fixture.speak(1, "hello").await;
+        fixture.driver.wait_for_utterances(1).await;
+
+        assert_eq!(fixture.driver.texts(), vec![vec!["hello".to_owned()]]);
+    }
+
+    #[tokio::test]
+    async fn system_messages_are_played_before_user_messages() {
+        let fixture = start(SessionConfig::default(), SpeakerNamePolicy::Never);
+
+        fixture.handle.pause().await.unwrap();
+        fixture.speak(1, "user").await;
+        fixture.announce("system").await;
+        settle().await;
+        assert_eq!(fixture.voice.call_count(), 2);
+
+        fixture.handle.resume().await.unwrap();
+        fixture.driver.wait_for_utterances(2).await;
+
+        assert_eq!(
+            fixture.driver.texts(),
+            vec![vec!["system".to_owned()], vec!["user".to_owned()]]
+        );
+    }
+
+    #[tokio::test]
+    async fn synthesis_is_throttled_by_prefetch_window() {
+        let config = SessionConfig {
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        for text in ["first", "second", "third"] {
+            fixture.speak(1, text).await;
+        }
+        fixture.driver.wait_for_utterances(1).await;
+        settle().await;
+        assert_eq!(fixture.voice.call_count(), 1);
+
+        fixture.driver.finish_track().await;
+        fixture.driver.wait_for_utterances(2).await;
+        settle().await;
+        assert_eq!(fixture.voice.call_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn system_messages_do_not_hold_prefetch_window() {
+        let config = SessionConfig {
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        fixture.announce("system").await;
+        fixture.driver.wait_for_utterances(1).await;
+        fixture.speak(1, "user").await;
+
+        fixture.driver.wait_for_utterances(2).await;
+        assert_eq!(
+            fixture.driver.texts(),
+            vec![vec!["system".to_owned()], vec!["user".to_owned()]]
+        );
+    }
+
+    #[tokio::test]
+    async fn drop_notices_are_throttled() {
+        let config = SessionConfig {
+            max_backlog: 1,
+            drop_policy: DropPolicy::DropOldest,
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        fixture.handle.pause().await.unwrap();
+        for text in ["1", "2", "3", "4", "5"] {
+            fixture.speak(1, text).await;
+        }
+        settle().await;
+
+        assert!(matches!(
+            fixture.notifier.notices().as_slice(),
+            [Notice::MessagesDropped { .. }]
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttled_drops_are_reported_once_interval_passes() {
+        let config = SessionConfig {
+            max_backlog: 1,
+            drop_policy: DropPolicy::DropOldest,
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        fixture.handle.pause().await.unwrap();
+        for text in ["1", "2", "3", "4", "5"] {
+            fixture.speak(1, text).await;
+        }
+        tokio::time::sleep(DROP_NOTICE_INTERVAL).await;
+        settle().await;
+
+        assert!(matches!(
+            fixture.notifier.notices().as_slice(),
+            [
+                Notice::MessagesDropped { count: 1 },
+                Notice::MessagesDropped { count: 2.. },
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn leave_command_leaves_voice_channel() {
+        let fixture = start(SessionConfig::default(), SpeakerNamePolicy::Never);
+
+        fixture.handle.leave().await.unwrap();
+
+        fixture.driver.wait_until(|recording| recording.left).await;
+    }
+
+    fn reconnecting_config() -> SessionConfig {
+        SessionConfig {
+            reconnect_attempts: 3,
+            reconnect_delay_ms: 1,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnects_and_keeps_reading_after_disconnect() {
+        let fixture = start(reconnecting_config(), SpeakerNamePolicy::Never);
+        fixture.driver.fail_reconnects(1);
+
+        fixture.driver.disconnect(true).await;
+        fixture.speak(1, "kept").await;
+
+        fixture.driver.wait_for_utterances(1).await;
+        let recording = fixture.driver.recording();
+        assert_eq!(recording.reconnects, 2);
+        assert!(!recording.left);
+        assert!(!recording.paused, "must resume after reconnecting");
+    }
+
+    #[tokio::test]
+    async fn session_stops_with_notice_after_reconnect_attempts_run_out() {
+        let fixture = start(reconnecting_config(), SpeakerNamePolicy::Never);
+        fixture.driver.fail_reconnects(3);
+
+        fixture.driver.disconnect(true).await;
+
+        fixture.driver.wait_until(|recording| recording.left).await;
+        assert_eq!(fixture.driver.recording().reconnects, 3);
+        assert!(matches!(
+            fixture.notifier.notices().as_slice(),
+            [Notice::ReconnectFailed { attempts: 3 }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn commands_are_handled_while_waiting_to_reconnect() {
+        let config = SessionConfig {
+            reconnect_attempts: 3,
+            reconnect_delay_ms: 60_000,
+            max_backlog: 1,
+            drop_policy: DropPolicy::DropOldest,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        fixture.driver.disconnect(true).await;
+        for text in ["1", "2", "3", "4", "5"] {
+            fixture.speak(1, text).await;
+        }
+        fixture.handle.leave().await.unwrap();
+
+        fixture.driver.wait_until(|recording| recording.left).await;
+        assert_eq!(fixture.driver.recording().reconnects, 0);
+        assert!(fixture.driver.recording().utterances.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unrecoverable_disconnect_stops_session() {
+        let fixture = start(SessionConfig::default(), SpeakerNamePolicy::Never);
+
+        fixture.driver.disconnect(false).await;
+
+        fixture.driver.wait_until(|recording| recording.left).await;
+        settle().await;
+        assert!(
+            fixture.handle.skip().await.is_err(),
+            "actor must be stopped"
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_session_announces_and_leaves() {
+        let fixture = start_with_idle_timeout(
+            SessionConfig::default(),
+            SpeakerNamePolicy::Never,
+            Some(Duration::from_millis(100)),
+        );
+
+        fixture.speak(1, "hello").await;
+        fixture.driver.wait_for_utterances(1).await;
+        fixture.driver.finish_track().await;
+
+        fixture.driver.wait_for_utterances(2).await;
+        assert_eq!(fixture.driver.texts()[1], vec!["idle".to_owned()]);
+        assert!(
+            !fixture.driver.recording().left,
+            "must wait for the farewell"
+        );
+
+        fixture.driver.finish_track().await;
+        fixture.driver.wait_until(|recording| recording.left).await;
+    }
+
+    #[tokio::test]
+    async fn idle_timer_waits_for_backlog_to_be_read() {
+        let fixture = start_with_idle_timeout(
+            SessionConfig::default(),
+            SpeakerNamePolicy::Never,
+            Some(Duration::from_millis(100)),
+        );
+
+        fixture.speak(1, "long").await;
+        fixture.driver.wait_for_utterances(1).await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(
+            fixture.driver.texts().len(),
+            1,
+            "must not leave while reading"
+        );
+
+        fixture.driver.finish_track().await;
+        fixture.driver.wait_for_utterances(2).await;
+        assert_eq!(fixture.driver.texts()[1], vec!["idle".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn paused_session_is_not_left_for_inactivity() {
+        let fixture = start_with_idle_timeout(
+            SessionConfig::default(),
+            SpeakerNamePolicy::Never,
+            Some(Duration::from_millis(100)),
+        );
+
+        fixture.handle.pause().await.unwrap();
+        fixture.speak(1, "held").await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let recording = fixture.driver.recording();
+        assert!(recording.utterances.is_empty() && !recording.left);
+        assert!(recording.paused, "held messages must not be resumed");
+    }
+
+    #[tokio::test]
+    async fn deleted_messages_are_not_played() {
+        let config = SessionConfig {
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        fixture.handle.pause().await.unwrap();
+        let synthesizing = fixture.speak(1, "first").await;
+        let waiting = fixture.speak(1, "second").await;
+        settle().await;
+        fixture.handle.cancel(waiting).await.unwrap();
+        fixture.handle.cancel(synthesizing).await.unwrap();
+        fixture.speak(1, "third").await;
+        fixture.handle.resume().await.unwrap();
+
+        fixture.driver.wait_for_utterances(1).await;
+        settle().await;
+        assert_eq!(fixture.driver.texts(), vec![vec!["third".to_owned()]]);
+    }
+
+    #[tokio::test]
+    async fn deleted_messages_are_removed_from_driver() {
+        let config = SessionConfig {
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        let enqueued = fixture.speak(1, "password").await;
+        fixture.driver.wait_for_utterances(1).await;
+        fixture.handle.cancel(enqueued).await.unwrap();
+        fixture.speak(1, "next").await;
+
+        // the removed utterance no longer holds the prefetch window
+        fixture.driver.wait_for_utterances(2).await;
+        assert_eq!(fixture.driver.recording().removed, vec![enqueued]);
+    }
+
+    #[tokio::test]
+    async fn edited_messages_are_read_with_new_text() {
+        let config = SessionConfig {
+            prefetch: 1,
+            ..Default::default()
+        };
+        let fixture = start(config, SpeakerNamePolicy::Never);
+
+        fixture.handle.pause().await.unwrap();
+        let synthesizing = fixture.speak(1, "frist").await;
+        let waiting = fixture.speak(1, "secnod").await;
+        settle().await;
+        fixture
+            .handle
+            .replace(synthesizing, "first".to_owned())
+            .await
+            .unwrap();
+        fixture
+            .handle
+            .replace(waiting, "second".to_owned())
+            .await
+            .unwrap();
+        fixture.handle.resume().await.unwrap();
+
+        fixture.driver.wait_for_utterances(1).await;
+        fixture.driver.finish_track().await;
+        fixture.driver.wait_for_utterances(2).await;
+        assert_eq!(
+            fixture.driver.texts(),
+            vec![vec!["first".to_owned()], vec!["second".to_owned()]]
+        );
+    }
+
+    #[tokio::test]
+    async fn lifecycle_events_are_emitted() {
+        let mut fixture = start(SessionConfig::default(), SpeakerNamePolicy::Never);
+
+        let spoken = fixture.speak(1, "hello").await;
+        fixture.driver.wait_for_utterances(1).await;
+        fixture.driver.finish_track().await;
+        fixture.speak(1, "cut off by leaving").await;
+        fixture.driver.wait_for_utterances(2).await;
+        fixture.handle.leave().await.unwrap();
+        fixture.driver.wait_until(|recording| recording.left).await;
+        settle().await;
+
+        let kinds: Vec<_> = std::iter::from_fn(|| fixture.events.try_recv().ok())
+            .map(|event| event.kind)
+            .collect();
+        assert!(matches!(
+            kinds.as_slice(),
+            [
+                SessionEventKind::MessageSpoken { message_id: Some(id) },
+                SessionEventKind::Ended {
+                    reason: EndReason::Left
+                },
+            ] if *id == spoken
+        ));
+    }
+
+    #[tokio::test]
+    async fn skipped_and_stopped_messages_are_not_reported_as_spoken() {
+        let mut fixture = start(SessionConfig::default(), SpeakerNamePolicy::Never);
 
-        let len = audios.len();
-        driver.enqueue(audios).await;
+        fixture.speak(1, "skipped").await;
+        fixture.speak(1, "stopped").await;
+        fixture.driver.wait_for_utterances(2).await;
+        fixture.handle.skip().await.unwrap();
+        fixture.handle.stop().await.unwrap();
+        fixture
+            .driver
+            .wait_until(|recording| recording.stopped > 0)
+            .await;
+        let spoken = fixture.speak(1, "spoken").await;
+        fixture.driver.wait_for_utterances(3).await;
+        fixture.driver.finish_track().await;
+        fixture.handle.leave().await.unwrap();
+        fixture.driver.wait_until(|recording| recording.left).await;
+        settle().await;
 
-        Ok(len)
+        let spoken_ids: Vec<_> = std::iter::from_fn(|| fixture.events.try_recv().ok())
+            .filter_map(|event| match event.kind {
+                SessionEventKind::MessageSpoken { message_id } => Some(message_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(spoken_ids, vec![Some(spoken)]);
     }
 }
@@ -1,43 +1,316 @@
 use crate::session::SessionCommand;
+use anyhow::anyhow;
 use async_trait::async_trait;
+use poise::serenity_prelude::MessageId;
+use songbird::events::context_data::DisconnectReason;
+use songbird::model::CloseCode;
+use songbird::tracks::{Queued, TrackHandle, TrackQueue};
 use songbird::{Call, CoreEvent, Event, EventContext, EventHandler, TrackEvent};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::{Mutex, mpsc};
 
 #[async_trait]
 pub trait AudioDriver: Sync + Send {
-    async fn enqueue(&self, audios: Vec<Vec<u8>>);
+    /// Enqueues audios as a single utterance, read for the message if any.
+    ///
+    /// Callers must not enqueue while paused, since an idle driver starts playing at once.
+    async fn enqueue(&self, message_id: Option<MessageId>, audios: Vec<Vec<u8>>);
+
+    /// Removes the utterance of the message, cutting it off if it is playing.
+    ///
+    /// End events are not fired for the removed tracks.
+    async fn remove(&self, message_id: MessageId) -> anyhow::Result<()>;
+
+    /// Cuts off the utterance currently playing, including its remaining segments.
+    ///
+    /// Returns whether an utterance was cut off. End events are not fired for its tracks.
+    async fn skip(&self) -> anyhow::Result<bool>;
+
+    /// Stops playback and flushes every utterance already enqueued.
+    ///
+    /// End events are not fired for the flushed tracks.
+    async fn stop(&self) -> anyhow::Result<()>;
+
+    /// Pauses playback at the next track boundary, letting the current track play to its end.
+    async fn pause(&self) -> anyhow::Result<()>;
+
+    /// Resumes playback paused by [`AudioDriver::pause`].
+    async fn resume(&self) -> anyhow::Result<()>;
+
+    /// Sets playback volume, applied to enqueued tracks as well. `1.0` is the original volume.
+    async fn set_volume(&self, volume: f32) -> anyhow::Result<()>;
 
     async fn leave(&self) -> anyhow::Result<()>;
 
+    /// Rejoins the voice channel after an unexpected disconnect.
+    async fn reconnect(&self) -> anyhow::Result<()>;
+
     async fn subscribe_to_end_event(&self, tx: mpsc::Sender<()>);
 
     async fn subscribe_to_disconnect_event(&self, tx: mpsc::Sender<SessionCommand>);
 }
 
+/// Tracks enqueued as a single utterance.
+struct Utterance {
+    message_id: Option<MessageId>,
+    handles: Vec<TrackHandle>,
+}
+
+impl Utterance {
+    fn contains(&self, track: &TrackHandle) -> bool {
+        self.handles.iter().any(|h| h.uuid() == track.uuid())
+    }
+}
+
 pub struct SongbirdDriver {
-    pub call: Arc<Mutex<Call>>,
+    call: Arc<Mutex<Call>>,
+    /// utterances in the same order as songbird queue
+    utterances: Mutex<VecDeque<Utterance>>,
+    /// tracks taken out of songbird queue while paused
+    held: Mutex<Vec<Queued>>,
+    /// tracks cut off or flushed, whose end is not reported
+    removed: Arc<std::sync::Mutex<Vec<TrackHandle>>>,
+    volume: Mutex<f32>,
 }
 
-struct SongbirdEventHandler<T: Send + Sync + Clone> {
-    tx: mpsc::Sender<T>,
-    result: T,
+impl SongbirdDriver {
+    pub fn new(call: Arc<Mutex<Call>>, volume: f32) -> Self {
+        Self {
+            call,
+            utterances: Mutex::new(VecDeque::new()),
+            held: Mutex::new(Vec::new()),
+            removed: Arc::new(std::sync::Mutex::new(Vec::new())),
+            volume: Mutex::new(volume),
+        }
+    }
+
+    /// Keeps the end of the tracks from being reported, which must be done before stopping them.
+    fn suppress_end<'a>(&self, tracks: impl IntoIterator<Item = &'a TrackHandle>) {
+        self.removed
+            .lock()
+            .expect("removed tracks poisoned")
+            .extend(tracks.into_iter().cloned());
+    }
+
+    /// Stops every track of the utterance, skipping the current one so that songbird moves on.
+    async fn cut(&self, queue: &TrackQueue, utterance: &Utterance) -> anyhow::Result<()> {
+        let current = queue
+            .current()
+            .filter(|current| utterance.contains(current));
+
+        // remove remaining segments first so that the queue does not advance into them.
+        let mut remaining = Vec::new();
+        let mut retain_track = |track: &Queued| {
+            let is_remaining = utterance.contains(track)
+                && current.as_ref().is_none_or(|c| c.uuid() != track.uuid());
+            if is_remaining {
+                remaining.push(track.handle());
+            }
+            !is_remaining
+        };
+        queue.modify_queue(|tracks| tracks.retain(&mut retain_track));
+        self.held.lock().await.retain(&mut retain_track);
+
+        self.suppress_end(remaining.iter().chain(current.iter()));
+        for track in &remaining {
+            let _ = track.stop();
+        }
+        if current.is_some() {
+            queue.skip()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reports the end of every track, except the removed ones.
+struct TrackEndHandler {
+    tx: mpsc::Sender<()>,
+    removed: Arc<std::sync::Mutex<Vec<TrackHandle>>>,
 }
+
 #[async_trait]
-impl<T: Send + Sync + Clone> EventHandler for SongbirdEventHandler<T> {
-    async fn act(&self, _: &EventContext<'_>) -> Option<Event> {
-        let _ = self.tx.send(self.result.clone()).await;
+impl EventHandler for TrackEndHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let EventContext::Track(tracks) = ctx else {
+            return None;
+        };
+
+        let ended = {
+            let mut removed = self.removed.lock().expect("removed tracks poisoned");
+            tracks
+                .iter()
+                .filter(|(_, track)| {
+                    let position = removed.iter().position(|h| h.uuid() == track.uuid());
+                    position.map(|i| removed.swap_remove(i)).is_none()
+                })
+                .count()
+        };
+        for _ in 0..ended {
+            let _ = self.tx.send(()).await;
+        }
+        None
+    }
+}
+
+/// Reports driver disconnects, telling whether rejoining may help.
+struct DisconnectHandler {
+    tx: mpsc::Sender<SessionCommand>,
+}
+
+#[async_trait]
+impl EventHandler for DisconnectHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::DriverDisconnect(data) = ctx {
+            // no reason means the disconnect was requested, e.g. by leaving
+            let recoverable = match data.reason {
+                Some(DisconnectReason::Io | DisconnectReason::TimedOut) => true,
+                // kicked, or the channel was deleted
+                Some(DisconnectReason::WsClosed(code)) => code != Some(CloseCode::Disconnected),
+                _ => false,
+            };
+            let _ = self
+                .tx
+                .send(SessionCommand::Disconnect { recoverable })
+                .await;
+        }
         None
     }
 }
 
 #[async_trait]
 impl AudioDriver for SongbirdDriver {
-    async fn enqueue(&self, data: Vec<Vec<u8>>) {
+    async fn enqueue(&self, message_id: Option<MessageId>, data: Vec<Vec<u8>>) {
         let mut call = self.call.lock().await;
+        let volume = *self.volume.lock().await;
+        let mut handles = Vec::with_capacity(data.len());
         for audio in data {
-            call.enqueue_input(audio.into()).await;
+            let handle = call.enqueue_input(audio.into()).await;
+            if let Err(e) = handle.set_volume(volume) {
+                tracing::warn!("Failed to set volume of enqueued track: {}", e);
+            }
+            handles.push(handle);
+        }
+
+        // forget utterances which songbird has already finished playing.
+        let mut queued: Vec<_> = call
+            .queue()
+            .current_queue()
+            .iter()
+            .map(|q| q.uuid())
+            .collect();
+        queued.extend(self.held.lock().await.iter().map(|q| q.uuid()));
+        let mut utterances = self.utterances.lock().await;
+        utterances.retain(|u| u.handles.iter().any(|h| queued.contains(&h.uuid())));
+        utterances.push_back(Utterance {
+            message_id,
+            handles,
+        });
+    }
+
+    async fn remove(&self, message_id: MessageId) -> anyhow::Result<()> {
+        let call = self.call.lock().await;
+        let queue = call.queue();
+
+        let mut utterances = self.utterances.lock().await;
+        let Some(position) = utterances
+            .iter()
+            .position(|u| u.message_id == Some(message_id))
+        else {
+            return Ok(());
+        };
+        let utterance = utterances
+            .remove(position)
+            .expect("position must be in range");
+
+        self.cut(queue, &utterance).await
+    }
+
+    async fn skip(&self) -> anyhow::Result<bool> {
+        let call = self.call.lock().await;
+        let queue = call.queue();
+        let Some(current) = queue.current() else {
+            return Ok(false);
+        };
+
+        let mut utterances = self.utterances.lock().await;
+        let Some(position) = utterances.iter().position(|u| u.contains(&current)) else {
+            // not enqueued by this driver, just skip the track itself.
+            queue.skip()?;
+            return Ok(false);
+        };
+
+        // utterances before the current one have already been played.
+        let utterance = utterances
+            .drain(..=position)
+            .next_back()
+            .expect("position must be in range");
+
+        self.cut(queue, &utterance).await?;
+        Ok(true)
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        let call = self.call.lock().await;
+        let mut held = self.held.lock().await;
+
+        let queued = call.queue().current_queue();
+        let held_handles: Vec<_> = held.iter().map(|track| track.handle()).collect();
+        self.suppress_end(queued.iter().chain(held_handles.iter()));
+
+        call.queue().stop();
+        for track in held.drain(..) {
+            let _ = track.stop();
         }
+        self.utterances.lock().await.clear();
+        Ok(())
+    }
+
+    async fn pause(&self) -> anyhow::Result<()> {
+        let call = self.call.lock().await;
+        let mut held = self.held.lock().await;
+
+        // keep the current track, so that songbird stops after it ends.
+        call.queue().modify_queue(|tracks| {
+            if tracks.len() > 1 {
+                held.extend(tracks.drain(1..));
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn resume(&self) -> anyhow::Result<()> {
+        let call = self.call.lock().await;
+        let mut held = self.held.lock().await;
+
+        call.queue().modify_queue(|tracks| {
+            let was_idle = tracks.is_empty();
+            tracks.extend(held.drain(..));
+
+            // songbird only advances the queue on track end, so kick the head manually.
+            match tracks.front() {
+                Some(head) if was_idle => head.play(),
+                _ => Ok(()),
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn set_volume(&self, volume: f32) -> anyhow::Result<()> {
+        let call = self.call.lock().await;
+        *self.volume.lock().await = volume;
+
+        for track in call.queue().current_queue() {
+            track.set_volume(volume)?;
+        }
+        for track in self.held.lock().await.iter() {
+            track.set_volume(volume)?;
+        }
+
+        Ok(())
     }
 
     async fn leave(&self) -> anyhow::Result<()> {
@@ -46,11 +319,26 @@ impl AudioDriver for SongbirdDriver {
         Ok(())
     }
 
+    async fn reconnect(&self) -> anyhow::Result<()> {
+        let join = {
+            let mut call = self.call.lock().await;
+            let channel = call
+                .current_channel()
+                .ok_or_else(|| anyhow!("No voice channel to reconnect to"))?;
+            call.join(channel).await?
+        };
+        join.await?;
+        Ok(())
+    }
+
     async fn subscribe_to_end_event(&self, tx: mpsc::Sender<()>) {
         let mut call = self.call.lock().await;
         call.add_global_event(
             Event::Track(TrackEvent::End),
-            SongbirdEventHandler { tx, result: () },
+            TrackEndHandler {
+                tx,
+                removed: self.removed.clone(),
+            },
         );
     }
 
@@ -58,10 +346,7 @@ impl AudioDriver for SongbirdDriver {
         let mut call = self.call.lock().await;
         call.add_global_event(
             Event::Core(CoreEvent::DriverDisconnect),
-            SongbirdEventHandler {
-                tx,
-                result: SessionCommand::Disconnect,
-            },
+            DisconnectHandler { tx },
         );
     }
 }
@@ -0,0 +1,141 @@
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Number of events kept for slow subscribers before they start missing some.
+const EVENT_BUS_CAPACITY: usize = 256;
+
+/// Something which happened in the session of a guild.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent {
+    pub guild_id: GuildId,
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEventKind {
+    Started {
+        voice_channel_id: ChannelId,
+        text_channel_id: ChannelId,
+    },
+    Ended {
+        reason: EndReason,
+    },
+    /// Emitted once the utterance has been played to its end.
+    /// `message_id` is `None` for announcements by the bot itself
+    MessageSpoken {
+        message_id: Option<MessageId>,
+    },
+    MessageDropped {
+        count: usize,
+    },
+    SynthesisFailed {
+        message_id: Option<MessageId>,
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+    /// left by command, or since everyone left the voice channel
+    Left,
+    Idle,
+    Disconnected,
+    ReconnectFailed,
+}
+
+/// Broadcasts session events of every guild to any number of subscribers.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<SessionEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { tx }
+    }
+
+    pub fn publish(&self, event: SessionEvent) {
+        // no one listening is not an error
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Publisher which tags events with the guild.
+    pub fn for_guild(&self, guild_id: GuildId) -> SessionEvents {
+        SessionEvents {
+            guild_id,
+            bus: self.clone(),
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Publishes events of a single session.
+#[derive(Debug, Clone)]
+pub struct SessionEvents {
+    guild_id: GuildId,
+    bus: EventBus,
+}
+
+impl SessionEvents {
+    pub fn guild_id(&self) -> GuildId {
+        self.guild_id
+    }
+
+    pub fn emit(&self, kind: SessionEventKind) {
+        self.bus.publish(SessionEvent {
+            guild_id: self.guild_id,
+            kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn subscribers_receive_events_tagged_with_guild() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+
+        bus.for_guild(GuildId::new(1))
+            .emit(SessionEventKind::MessageDropped { count: 2 });
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.guild_id, GuildId::new(1));
+        assert!(matches!(
+            event.kind,
+            SessionEventKind::MessageDropped { count: 2 }
+        ));
+    }
+
+    #[test]
+    fn serializes_as_flat_tagged_object() {
+        let event = SessionEvent {
+            guild_id: GuildId::new(1),
+            kind: SessionEventKind::Ended {
+                reason: EndReason::ReconnectFailed,
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "guild_id": "1", "type": "ended", "reason": "reconnect_failed" })
+        );
+    }
+}
@@ -1,37 +1,59 @@
-use crate::session::SessionHandle;
+use crate::session::repository::{PersistedSession, SessionRepository};
+use crate::session::{ReadingMode, SessionHandle};
 use anyhow::anyhow;
 use dashmap::DashMap;
-use poise::serenity_prelude::{ChannelId, GuildId};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
 
 #[derive(Debug, Clone)]
 pub struct SessionInfo {
     pub handle: SessionHandle,
+    /// channel the session was started from, where notices are posted
     pub text_channel: ChannelId,
+    /// channels read in addition to `text_channel`
+    pub extra_text_channels: Vec<ChannelId>,
     pub voice_channel: ChannelId,
+    pub reading_mode: ReadingMode,
+    /// user the bot moves along with, set by `/follow`
+    pub followed: Option<UserId>,
+}
+
+impl SessionInfo {
+    /// All channels read by the session, starting with the primary one.
+    pub fn text_channels(&self) -> impl Iterator<Item = ChannelId> + '_ {
+        std::iter::once(self.text_channel).chain(self.extra_text_channels.iter().copied())
+    }
 }
 
-#[derive(Debug)]
 pub struct SessionManager {
     sessions: DashMap<GuildId, SessionInfo>,
 
     text_channels: DashMap<ChannelId, GuildId>,
     voice_channels: DashMap<ChannelId, GuildId>,
+
+    /// keeps active sessions across restarts
+    repository: SessionRepository,
 }
 
 impl SessionManager {
-    pub fn new() -> Self {
+    pub fn new(repository: SessionRepository) -> Self {
         Self {
             sessions: DashMap::new(),
             text_channels: DashMap::new(),
             voice_channels: DashMap::new(),
+            repository,
         }
     }
 
-    pub fn register(
+    pub fn repository(&self) -> &SessionRepository {
+        &self.repository
+    }
+
+    pub async fn register(
         &self,
         guild_id: GuildId,
         text_channel: ChannelId,
         voice_channel: ChannelId,
+        reading_mode: ReadingMode,
         handle: SessionHandle,
     ) {
         self.sessions.insert(
@@ -39,7 +61,10 @@ impl SessionManager {
             SessionInfo {
                 handle,
                 text_channel,
+                extra_text_channels: Vec::new(),
                 voice_channel,
+                reading_mode,
+                followed: None,
             },
         );
         self.text_channels.insert(text_channel, guild_id);
@@ -49,6 +74,8 @@ impl SessionManager {
             guild_id,
             text_channel
         );
+
+        self.persist(guild_id).await;
     }
 
     pub fn get(&self, guild_id: GuildId) -> Option<SessionInfo> {
@@ -60,12 +87,106 @@ impl SessionManager {
         self.get(*guild_id)
     }
 
+    /// Finds the session which reads messages posted in the channel.
+    ///
+    /// Unlike [`SessionManager::get_by_text_channel`], this takes the reading mode into account,
+    /// so the chat of the voice channel is included and the primary text channel may be excluded.
+    pub fn get_by_reading_channel(&self, channel: ChannelId) -> Option<SessionInfo> {
+        if let Some(session) = self.get_by_text_channel(channel)
+            && (channel != session.text_channel || session.reading_mode.reads_text_channel())
+        {
+            return Some(session);
+        }
+
+        self.get_by_voice_channel(channel)
+            .filter(|session| session.reading_mode.reads_voice_chat())
+    }
+
+    /// Adds a channel to be read by the session of the guild.
+    pub async fn add_text_channel(
+        &self,
+        guild_id: GuildId,
+        channel: ChannelId,
+    ) -> anyhow::Result<()> {
+        {
+            let mut session_entry = self
+                .sessions
+                .get_mut(&guild_id)
+                .ok_or(anyhow!("Session info not found for guild {}", guild_id))?;
+
+            if session_entry.text_channels().any(|c| c == channel) {
+                return Ok(());
+            }
+
+            session_entry.extra_text_channels.push(channel);
+        }
+        self.text_channels.insert(channel, guild_id);
+
+        self.persist(guild_id).await;
+
+        Ok(())
+    }
+
+    /// Stops reading a channel added by [`SessionManager::add_text_channel`].
+    ///
+    /// The primary text channel cannot be removed.
+    pub async fn remove_text_channel(
+        &self,
+        guild_id: GuildId,
+        channel: ChannelId,
+    ) -> anyhow::Result<()> {
+        {
+            let mut session_entry = self
+                .sessions
+                .get_mut(&guild_id)
+                .ok_or(anyhow!("Session info not found for guild {}", guild_id))?;
+
+            if session_entry.text_channel == channel {
+                return Err(anyhow!(
+                    "Primary text channel {} cannot be removed",
+                    channel
+                ));
+            }
+
+            let len = session_entry.extra_text_channels.len();
+            session_entry.extra_text_channels.retain(|&c| c != channel);
+            if session_entry.extra_text_channels.len() == len {
+                return Err(anyhow!("Channel {} is not read by the session", channel));
+            }
+        }
+        self.text_channels.remove(&channel);
+
+        self.persist(guild_id).await;
+
+        Ok(())
+    }
+
+    /// Makes the bot move along with the user, or stop following with `None`.
+    pub async fn set_followed(
+        &self,
+        guild_id: GuildId,
+        user: Option<UserId>,
+    ) -> anyhow::Result<()> {
+        {
+            let mut session_entry = self
+                .sessions
+                .get_mut(&guild_id)
+                .ok_or(anyhow!("Session info not found for guild {}", guild_id))?;
+
+            session_entry.followed = user;
+        }
+
+        self.persist(guild_id).await;
+
+        Ok(())
+    }
+
     pub fn get_by_voice_channel(&self, voice_channel: ChannelId) -> Option<SessionInfo> {
         let guild_id = self.voice_channels.get(&voice_channel)?;
         self.get(*guild_id)
     }
 
-    pub fn update_voice_channel(
+    pub async fn update_voice_channel(
         &self,
         old: ChannelId,
         new: ChannelId,
@@ -76,29 +197,31 @@ impl SessionManager {
             .ok_or(anyhow!("Guild ID not found for voice channel {}", old))?
             .to_owned();
 
-        let mut session_entry = self
-            .sessions
-            .get_mut(&guild_id)
-            .ok_or(anyhow!("Session info not found for guild {}", guild_id))?;
+        {
+            let mut session_entry = self
+                .sessions
+                .get_mut(&guild_id)
+                .ok_or(anyhow!("Session info not found for guild {}", guild_id))?;
 
-        session_entry.voice_channel = new;
+            session_entry.voice_channel = new;
+        }
         self.voice_channels.remove(&old);
         self.voice_channels.insert(new, guild_id);
 
+        self.persist(guild_id).await;
+
         Ok(())
     }
 
-    pub fn remove(&self, guild_id: GuildId) {
+    pub async fn remove(&self, guild_id: GuildId) {
         if let Some((_, session_info)) = self.sessions.remove(&guild_id) {
-            if self
-                .text_channels
-                .remove(&session_info.text_channel)
-                .is_none()
-            {
-                tracing::warn!(
-                    "Inconsistency: Text channel index was missing for guild {}",
-                    guild_id
-                );
+            for text_channel in session_info.text_channels() {
+                if self.text_channels.remove(&text_channel).is_none() {
+                    tracing::warn!(
+                        "Inconsistency: Text channel index was missing for guild {}",
+                        guild_id
+                    );
+                }
             }
 
             if self
@@ -113,12 +236,235 @@ impl SessionManager {
             }
 
             tracing::info!("Removed session for guild: {}", guild_id);
+
+            if let Err(e) = self.repository.delete(guild_id).await {
+                tracing::error!(
+                    "Failed to delete persisted session for {}: {:?}",
+                    guild_id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Saves the current channels of the session, so that it can be restored after restart.
+    async fn persist(&self, guild_id: GuildId) {
+        let Some(session) = self.get(guild_id) else {
+            return;
+        };
+
+        let result = self
+            .repository
+            .save(PersistedSession {
+                guild: guild_id,
+                voice: session.voice_channel,
+                text: session.text_channel,
+                extra_text: session.extra_text_channels,
+                mode: session.reading_mode,
+                followed: session.followed,
+            })
+            .await;
+
+        if let Err(e) = result {
+            tracing::error!("Failed to persist session for {}: {:?}", guild_id, e);
         }
     }
 }
 
-impl Default for SessionManager {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redb::Database;
+    use redb::backends::InMemoryBackend;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    fn create_manager() -> SessionManager {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .expect("must create in-memory database");
+        SessionManager::new(SessionRepository::new(Arc::new(db)))
+    }
+
+    fn create_handle() -> SessionHandle {
+        let (tx, _) = mpsc::channel(1);
+        SessionHandle::new(tx)
+    }
+
+    #[tokio::test]
+    async fn added_text_channels_resolve_to_session() {
+        let manager = create_manager();
+        let guild_id = GuildId::new(1);
+        manager
+            .register(
+                guild_id,
+                ChannelId::new(10),
+                ChannelId::new(20),
+                ReadingMode::TextChannel,
+                create_handle(),
+            )
+            .await;
+
+        manager
+            .add_text_channel(guild_id, ChannelId::new(11))
+            .await
+            .unwrap();
+        manager
+            .add_text_channel(guild_id, ChannelId::new(12))
+            .await
+            .unwrap();
+
+        for channel in [10, 11, 12] {
+            let session = manager
+                .get_by_text_channel(ChannelId::new(channel))
+                .expect("session must be found");
+            assert_eq!(session.text_channel, ChannelId::new(10));
+        }
+    }
+
+    #[tokio::test]
+    async fn removed_text_channel_is_no_longer_read() {
+        let manager = create_manager();
+        let guild_id = GuildId::new(1);
+        manager
+            .register(
+                guild_id,
+                ChannelId::new(10),
+                ChannelId::new(20),
+                ReadingMode::TextChannel,
+                create_handle(),
+            )
+            .await;
+        manager
+            .add_text_channel(guild_id, ChannelId::new(11))
+            .await
+            .unwrap();
+
+        manager
+            .remove_text_channel(guild_id, ChannelId::new(11))
+            .await
+            .unwrap();
+
+        assert!(manager.get_by_text_channel(ChannelId::new(11)).is_none());
+        assert!(
+            manager
+                .remove_text_channel(guild_id, ChannelId::new(10))
+                .await
+                .is_err(),
+            "primary channel must not be removed"
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_session_clears_every_text_channel() {
+        let manager = create_manager();
+        let guild_id = GuildId::new(1);
+        manager
+            .register(
+                guild_id,
+                ChannelId::new(10),
+                ChannelId::new(20),
+                ReadingMode::TextChannel,
+                create_handle(),
+            )
+            .await;
+        manager
+            .add_text_channel(guild_id, ChannelId::new(11))
+            .await
+            .unwrap();
+
+        manager.remove(guild_id).await;
+
+        assert!(manager.get_by_text_channel(ChannelId::new(10)).is_none());
+        assert!(manager.get_by_text_channel(ChannelId::new(11)).is_none());
+    }
+
+    #[tokio::test]
+    async fn reading_mode_selects_text_channel_or_voice_chat() {
+        let manager = create_manager();
+        for (guild, mode) in [
+            (1, ReadingMode::TextChannel),
+            (2, ReadingMode::VoiceChat),
+            (3, ReadingMode::Both),
+        ] {
+            manager
+                .register(
+                    GuildId::new(guild),
+                    ChannelId::new(guild * 10),
+                    ChannelId::new(guild * 10 + 1),
+                    mode,
+                    create_handle(),
+                )
+                .await;
+        }
+
+        let reads = |channel| {
+            manager
+                .get_by_reading_channel(ChannelId::new(channel))
+                .is_some()
+        };
+        assert!(reads(10) && !reads(11));
+        assert!(!reads(20) && reads(21));
+        assert!(reads(30) && reads(31));
+    }
+
+    #[tokio::test]
+    async fn followed_session_is_found_by_new_voice_channel_after_move() {
+        let manager = create_manager();
+        let guild_id = GuildId::new(1);
+        manager
+            .register(
+                guild_id,
+                ChannelId::new(10),
+                ChannelId::new(20),
+                ReadingMode::TextChannel,
+                create_handle(),
+            )
+            .await;
+        manager
+            .set_followed(guild_id, Some(UserId::new(100)))
+            .await
+            .unwrap();
+
+        manager
+            .update_voice_channel(ChannelId::new(20), ChannelId::new(21))
+            .await
+            .unwrap();
+
+        assert!(manager.get_by_voice_channel(ChannelId::new(20)).is_none());
+        let session = manager
+            .get_by_voice_channel(ChannelId::new(21))
+            .expect("session must follow the move");
+        assert_eq!(session.followed, Some(UserId::new(100)));
+        let persisted = manager.repository().find_all().await.unwrap();
+        assert_eq!(persisted[0].followed, Some(UserId::new(100)));
+    }
+
+    #[tokio::test]
+    async fn added_text_channels_are_persisted() {
+        let manager = create_manager();
+        let guild_id = GuildId::new(1);
+        manager
+            .register(
+                guild_id,
+                ChannelId::new(10),
+                ChannelId::new(20),
+                ReadingMode::TextChannel,
+                create_handle(),
+            )
+            .await;
+        for channel in [11, 12] {
+            manager
+                .add_text_channel(guild_id, ChannelId::new(channel))
+                .await
+                .unwrap();
+        }
+        manager
+            .remove_text_channel(guild_id, ChannelId::new(11))
+            .await
+            .unwrap();
+
+        let persisted = manager.repository().find_all().await.unwrap();
+        assert_eq!(persisted[0].extra_text, vec![ChannelId::new(12)]);
     }
 }
@@ -1,11 +1,85 @@
 use crate::tts::Voice;
-use poise::serenity_prelude::UserId;
+use poise::serenity_prelude::{MessageId, UserId};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 pub mod actor;
 pub mod driver;
+pub mod event;
 pub mod manager;
+pub mod notifier;
+pub mod queue;
+pub mod repository;
+pub mod webhook;
+
+/// Which text chats are read by a session.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadingMode {
+    /// the text channel the session was started from
+    #[default]
+    #[name = "text-channel"]
+    TextChannel,
+    /// the built-in text chat of the voice channel
+    #[name = "voice-chat"]
+    VoiceChat,
+    #[name = "both"]
+    Both,
+}
+
+impl ReadingMode {
+    pub fn reads_text_channel(self) -> bool {
+        matches!(self, Self::TextChannel | Self::Both)
+    }
+
+    pub fn reads_voice_chat(self) -> bool {
+        matches!(self, Self::VoiceChat | Self::Both)
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::TextChannel => 0,
+            Self::VoiceChat => 1,
+            Self::Both => 2,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::VoiceChat,
+            2 => Self::Both,
+            _ => Self::TextChannel,
+        }
+    }
+}
+
+/// When the name of the speaker is read before a message.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeakerNamePolicy {
+    #[name = "always"]
+    Always,
+    #[default]
+    #[name = "on-change"]
+    OnChange,
+    /// on speaker change, or after silence of [`NameAnnouncement::silence`]
+    #[name = "on-change-or-silence"]
+    OnChangeOrSilence,
+    #[name = "never"]
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameAnnouncement {
+    pub policy: SpeakerNamePolicy,
+    pub silence: Duration,
+}
 
 #[derive(Clone, Copy)]
 pub enum Priority {
@@ -16,6 +90,7 @@ pub enum Priority {
 #[derive(Debug, Clone)]
 pub struct Speaker {
     user_id: UserId,
+    /// announcement read before the message, already formatted for the voice
     name: String,
 }
 
@@ -31,11 +106,30 @@ pub enum SessionCommand {
         text: String,
         voice: Arc<dyn Voice>,
         speaker: Option<Speaker>,
+        /// message being read, to be cancelled or replaced while waiting
+        message_id: Option<MessageId>,
         priority: Priority,
     },
+    /// drops the message if it has not been played yet
+    Cancel(MessageId),
+    /// reads the new text instead, if the message has not been played yet
+    Replace {
+        message_id: MessageId,
+        text: String,
+    },
+    Skip,
     Stop,
-    Leave,      // user intentionally disconnected by command
-    Disconnect, // internal usage: Songbird drive
+    Pause,
+    Resume,
+    SetVolume(f32),
+    SetNameAnnouncement(NameAnnouncement),
+    /// disables auto leave if `None`
+    SetIdleTimeout(Option<Duration>),
+    Leave, // user intentionally disconnected by command
+    /// internal usage: the songbird driver disconnected, and rejoining may help if `recoverable`
+    Disconnect {
+        recoverable: bool,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -53,12 +147,14 @@ impl SessionHandle {
         text: String,
         voice: Arc<dyn Voice>,
         speaker: Speaker,
+        message_id: MessageId,
     ) -> anyhow::Result<()> {
         self.tx
             .send(SessionCommand::Speak {
                 text,
                 voice,
                 speaker: Some(speaker),
+                message_id: Some(message_id),
                 priority: Priority::User,
             })
             .await?;
@@ -71,19 +167,285 @@ impl SessionHandle {
                 text,
                 voice,
                 speaker: None,
+                message_id: None,
                 priority: Priority::System,
             })
             .await?;
         Ok(())
     }
 
+    pub async fn cancel(&self, message_id: MessageId) -> anyhow::Result<()> {
+        self.tx.send(SessionCommand::Cancel(message_id)).await?;
+        Ok(())
+    }
+
+    pub async fn replace(&self, message_id: MessageId, text: String) -> anyhow::Result<()> {
+        self.tx
+            .send(SessionCommand::Replace { message_id, text })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn skip(&self) -> anyhow::Result<()> {
+        self.tx.send(SessionCommand::Skip).await?;
+        Ok(())
+    }
+
     pub async fn stop(&self) -> anyhow::Result<()> {
         self.tx.send(SessionCommand::Stop).await?;
         Ok(())
     }
 
+    pub async fn pause(&self) -> anyhow::Result<()> {
+        self.tx.send(SessionCommand::Pause).await?;
+        Ok(())
+    }
+
+    pub async fn resume(&self) -> anyhow::Result<()> {
+        self.tx.send(SessionCommand::Resume).await?;
+        Ok(())
+    }
+
+    pub async fn set_volume(&self, volume: f32) -> anyhow::Result<()> {
+        self.tx.send(SessionCommand::SetVolume(volume)).await?;
+        Ok(())
+    }
+
+    pub async fn set_name_announcement(
+        &self,
+        announcement: NameAnnouncement,
+    ) -> anyhow::Result<()> {
+        self.tx
+            .send(SessionCommand::SetNameAnnouncement(announcement))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_idle_timeout(&self, after: Option<Duration>) -> anyhow::Result<()> {
+        self.tx.send(SessionCommand::SetIdleTimeout(after)).await?;
+        Ok(())
+    }
+
     pub async fn leave(&self) -> anyhow::Result<()> {
         self.tx.send(SessionCommand::Leave).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub mod test_utils {
+    use crate::session::SessionCommand;
+    use crate::session::driver::AudioDriver;
+    use crate::session::notifier::{Notice, Notifier};
+    use async_trait::async_trait;
+    use poise::serenity_prelude::MessageId;
+    use std::sync::{Mutex, MutexGuard};
+    use std::time::Duration;
+    use tokio::sync::{Notify, mpsc};
+
+    /// How long [`RecordingDriver`] waits for an expected state before failing the test.
+    const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[derive(Debug, Default)]
+    pub struct Recording {
+        /// audios of each enqueued utterance, in enqueued order
+        pub utterances: Vec<Vec<Vec<u8>>>,
+        /// messages whose utterance was removed
+        pub removed: Vec<MessageId>,
+        pub skipped: usize,
+        pub stopped: usize,
+        pub paused: bool,
+        pub volume: Option<f32>,
+        pub left: bool,
+        pub reconnects: usize,
+        /// number of upcoming reconnect attempts to fail
+        pub failing_reconnects: usize,
+        end_tx: Option<mpsc::Sender<()>>,
+        disconnect_tx: Option<mpsc::Sender<SessionCommand>>,
+    }
+
+    impl Recording {
+        /// Enqueued utterances with each segment decoded as text, which is what
+        /// [`MockVoice`](crate::tts::test_utils::MockVoice) generates.
+        pub fn texts(&self) -> Vec<Vec<String>> {
+            self.utterances
+                .iter()
+                .map(|audios| {
+                    audios
+                        .iter()
+                        .map(|audio| String::from_utf8_lossy(audio).into_owned())
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    /// Audio driver which records what the session asks it to do, instead of playing.
+    ///
+    /// End of track and disconnect events are fired on demand by tests.
+    #[derive(Default)]
+    pub struct RecordingDriver {
+        recording: Mutex<Recording>,
+        changed: Notify,
+    }
+
+    impl RecordingDriver {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn recording(&self) -> MutexGuard<'_, Recording> {
+            self.recording.lock().unwrap()
+        }
+
+        pub fn texts(&self) -> Vec<Vec<String>> {
+            self.recording().texts()
+        }
+
+        /// Makes the next `count` reconnect attempts fail.
+        pub fn fail_reconnects(&self, count: usize) {
+            self.record(|recording| recording.failing_reconnects = count);
+        }
+
+        /// Waits until the recording satisfies `condition`, panicking on timeout.
+        pub async fn wait_until(&self, condition: impl Fn(&Recording) -> bool) {
+            let wait = async {
+                loop {
+                    let notified = self.changed.notified();
+                    tokio::pin!(notified);
+                    notified.as_mut().enable();
+
+                    if condition(&self.recording.lock().unwrap()) {
+                        return;
+                    }
+                    notified.await;
+                }
+            };
+
+            tokio::time::timeout(WAIT_TIMEOUT, wait)
+                .await
+                .expect("driver must reach the expected state");
+        }
+
+        pub async fn wait_for_utterances(&self, count: usize) {
+            self.wait_until(|recording| recording.utterances.len() >= count)
+                .await;
+        }
+
+        /// Fires the end event of a single track.
+        pub async fn finish_track(&self) {
+            self.wait_until(|recording| recording.end_tx.is_some())
+                .await;
+            let tx = self.recording.lock().unwrap().end_tx.clone();
+            tx.expect("end event must be subscribed")
+                .send(())
+                .await
+                .expect("end event receiver must be alive");
+        }
+
+        /// Fires the disconnect event, as if the connection to the voice server was lost.
+        pub async fn disconnect(&self, recoverable: bool) {
+            self.wait_until(|recording| recording.disconnect_tx.is_some())
+                .await;
+            let tx = self.recording.lock().unwrap().disconnect_tx.clone();
+            tx.expect("disconnect event must be subscribed")
+                .send(SessionCommand::Disconnect { recoverable })
+                .await
+                .expect("disconnect event receiver must be alive");
+        }
+
+        fn record(&self, f: impl FnOnce(&mut Recording)) {
+            f(&mut self.recording.lock().unwrap());
+            self.changed.notify_waiters();
+        }
+    }
+
+    #[async_trait]
+    impl AudioDriver for RecordingDriver {
+        async fn enqueue(&self, _message_id: Option<MessageId>, audios: Vec<Vec<u8>>) {
+            self.record(|recording| recording.utterances.push(audios));
+        }
+
+        async fn remove(&self, message_id: MessageId) -> anyhow::Result<()> {
+            self.record(|recording| recording.removed.push(message_id));
+            Ok(())
+        }
+
+        async fn skip(&self) -> anyhow::Result<bool> {
+            self.record(|recording| recording.skipped += 1);
+            Ok(true)
+        }
+
+        async fn stop(&self) -> anyhow::Result<()> {
+            self.record(|recording| recording.stopped += 1);
+            Ok(())
+        }
+
+        async fn pause(&self) -> anyhow::Result<()> {
+            self.record(|recording| recording.paused = true);
+            Ok(())
+        }
+
+        async fn resume(&self) -> anyhow::Result<()> {
+            self.record(|recording| recording.paused = false);
+            Ok(())
+        }
+
+        async fn set_volume(&self, volume: f32) -> anyhow::Result<()> {
+            self.record(|recording| recording.volume = Some(volume));
+            Ok(())
+        }
+
+        async fn leave(&self) -> anyhow::Result<()> {
+            self.record(|recording| recording.left = true);
+            Ok(())
+        }
+
+        async fn reconnect(&self) -> anyhow::Result<()> {
+            let mut failed = false;
+            self.record(|recording| {
+                recording.reconnects += 1;
+                if recording.failing_reconnects > 0 {
+                    recording.failing_reconnects -= 1;
+                    failed = true;
+                }
+            });
+
+            match failed {
+                true => Err(anyhow::anyhow!("reconnect failed")),
+                false => Ok(()),
+            }
+        }
+
+        async fn subscribe_to_end_event(&self, tx: mpsc::Sender<()>) {
+            self.record(|recording| recording.end_tx = Some(tx));
+        }
+
+        async fn subscribe_to_disconnect_event(&self, tx: mpsc::Sender<SessionCommand>) {
+            self.record(|recording| recording.disconnect_tx = Some(tx));
+        }
+    }
+
+    /// Notifier which keeps notices instead of posting them.
+    #[derive(Default)]
+    pub struct RecordingNotifier {
+        notices: Mutex<Vec<Notice>>,
+    }
+
+    impl RecordingNotifier {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn notices(&self) -> Vec<Notice> {
+            self.notices.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, notice: Notice) {
+            self.notices.lock().unwrap().push(notice);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::localization::Locales;
+use async_trait::async_trait;
+use fluent::fluent_args;
+use poise::serenity_prelude::{ChannelId, Http};
+use std::sync::Arc;
+
+/// Notices posted by a session into its text channel.
+#[derive(Debug, Clone)]
+pub enum Notice {
+    MessagesDropped {
+        count: usize,
+    },
+    /// the session ended since rejoining the voice channel kept failing
+    ReconnectFailed {
+        attempts: usize,
+    },
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, notice: Notice);
+}
+
+/// Posts localized notices into the text channel of a session.
+pub struct DiscordNotifier {
+    http: Arc<Http>,
+    channel_id: ChannelId,
+    locale: String,
+    locales: Arc<Locales>,
+}
+
+impl DiscordNotifier {
+    pub fn new(
+        http: Arc<Http>,
+        channel_id: ChannelId,
+        locale: String,
+        locales: Arc<Locales>,
+    ) -> Self {
+        Self {
+            http,
+            channel_id,
+            locale,
+            locales,
+        }
+    }
+
+    fn render(&self, notice: &Notice) -> anyhow::Result<String> {
+        match notice {
+            Notice::MessagesDropped { count } => self.locales.resolve(
+                &self.locale,
+                "messages-dropped-notice",
+                None,
+                Some(&fluent_args!["count" => *count]),
+            ),
+            Notice::ReconnectFailed { attempts } => self.locales.resolve(
+                &self.locale,
+                "reconnect-failed-notice",
+                None,
+                Some(&fluent_args!["attempts" => *attempts]),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    async fn notify(&self, notice: Notice) {
+        let content = match self.render(&notice) {
+            Ok(content) => content,
+            Err(err) => {
+                tracing::error!("Failed to render notice {:?}: {:?}", notice, err);
+                return;
+            }
+        };
+
+        if let Err(err) = self.channel_id.say(&self.http, content).await {
+            tracing::warn!("Failed to post notice {:?}: {:?}", notice, err);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// Policy applied when a message arrives while the backlog is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all(deserialize = "snake_case"))]
+pub enum DropPolicy {
+    /// Drop the oldest waiting message to make room for the new one.
+    #[default]
+    DropOldest,
+    /// Drop the new message and keep the backlog as is.
+    DropNewest,
+    /// Drop the whole backlog and keep only the new message,
+    /// so that reading catches up with the conversation at once.
+    Collapse,
+}
+
+/// Bounded FIFO queue of messages waiting to be read in a session.
+pub struct ReadingQueue<T> {
+    items: Mutex<VecDeque<T>>,
+    notify: Notify,
+    max_backlog: usize,
+    policy: DropPolicy,
+}
+
+impl<T> ReadingQueue<T> {
+    pub fn new(max_backlog: usize, policy: DropPolicy) -> Self {
+        Self {
+            items: Mutex::new(VecDeque::new()),
+            notify: Notify::new(),
+            max_backlog: max_backlog.max(1),
+            policy,
+        }
+    }
+
+    /// Pushes an item, applying the drop policy when the backlog is full.
+    ///
+    /// Returns the number of items dropped by this push.
+    pub fn push(&self, item: T) -> usize {
+        let mut items = self.items.lock().expect("reading queue poisoned");
+
+        let dropped = if items.len() < self.max_backlog {
+            items.push_back(item);
+            0
+        } else {
+            match self.policy {
+                DropPolicy::DropOldest => {
+                    items.pop_front();
+                    items.push_back(item);
+                    1
+                }
+                DropPolicy::DropNewest => 1,
+                DropPolicy::Collapse => {
+                    let dropped = items.len();
+                    items.clear();
+                    items.push_back(item);
+                    dropped
+                }
+            }
+        };
+
+        drop(items);
+        self.notify.notify_one();
+
+        dropped
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        self.items
+            .lock()
+            .expect("reading queue poisoned")
+            .pop_front()
+    }
+
+    /// Waits until an item is available and pops it.
+    ///
+    /// Cancel safe: an item is never lost when the future is dropped.
+    pub async fn pop(&self) -> T {
+        loop {
+            if let Some(item) = self.try_pop() {
+                return item;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Removes waiting items matching the predicate, returning how many were removed.
+    pub fn remove_if(&self, mut predicate: impl FnMut(&T) -> bool) -> usize {
+        let mut items = self.items.lock().expect("reading queue poisoned");
+        let len = items.len();
+        items.retain(|item| !predicate(item));
+        len - items.len()
+    }
+
+    /// Applies `f` to the first waiting item matching the predicate.
+    ///
+    /// Returns whether such an item was found.
+    pub fn update_first(
+        &self,
+        mut predicate: impl FnMut(&T) -> bool,
+        f: impl FnOnce(&mut T),
+    ) -> bool {
+        let mut items = self.items.lock().expect("reading queue poisoned");
+        match items.iter_mut().find(|item| predicate(item)) {
+            Some(item) => {
+                f(item);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes every waiting item, returning how many were removed.
+    pub fn clear(&self) -> usize {
+        let mut items = self.items.lock().expect("reading queue poisoned");
+        let len = items.len();
+        items.clear();
+        len
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.lock().expect("reading queue poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(queue: &ReadingQueue<i32>) -> Vec<i32> {
+        std::iter::from_fn(|| queue.try_pop()).collect()
+    }
+
+    #[test]
+    fn keeps_fifo_order_within_backlog() {
+        let queue = ReadingQueue::new(3, DropPolicy::DropOldest);
+
+        assert_eq!(queue.push(1), 0);
+        assert_eq!(queue.push(2), 0);
+        assert_eq!(queue.push(3), 0);
+
+        assert_eq!(drain(&queue), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn drop_oldest_discards_head() {
+        let queue = ReadingQueue::new(2, DropPolicy::DropOldest);
+        queue.push(1);
+        queue.push(2);
+
+        assert_eq!(queue.push(3), 1);
+        assert_eq!(drain(&queue), vec![2, 3]);
+    }
+
+    #[test]
+    fn drop_newest_discards_incoming() {
+        let queue = ReadingQueue::new(2, DropPolicy::DropNewest);
+        queue.push(1);
+        queue.push(2);
+
+        assert_eq!(queue.push(3), 1);
+        assert_eq!(drain(&queue), vec![1, 2]);
+    }
+
+    #[test]
+    fn collapse_keeps_only_incoming() {
+        let queue = ReadingQueue::new(3, DropPolicy::Collapse);
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+
+        assert_eq!(queue.push(4), 3);
+        assert_eq!(drain(&queue), vec![4]);
+    }
+
+    #[test]
+    fn clear_reports_removed_count() {
+        let queue = ReadingQueue::new(5, DropPolicy::DropOldest);
+        queue.push(1);
+        queue.push(2);
+
+        assert_eq!(queue.clear(), 2);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn remove_if_and_update_first_touch_matching_items() {
+        let queue = ReadingQueue::new(5, DropPolicy::DropOldest);
+        for item in [1, 2, 3, 4] {
+            queue.push(item);
+        }
+
+        assert_eq!(queue.remove_if(|&item| item % 2 == 0), 2);
+        assert!(queue.update_first(|&item| item == 3, |item| *item = 30));
+        assert!(!queue.update_first(|&item| item == 2, |item| *item = 20));
+        assert_eq!(drain(&queue), vec![1, 30]);
+    }
+
+    #[tokio::test]
+    async fn pop_waits_for_push() {
+        let queue = std::sync::Arc::new(ReadingQueue::new(5, DropPolicy::DropOldest));
+
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.pop().await })
+        };
+        tokio::task::yield_now().await;
+        queue.push(42);
+
+        assert_eq!(waiter.await.unwrap(), 42);
+    }
+}
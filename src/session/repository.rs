@@ -0,0 +1,160 @@
+use crate::session::ReadingMode;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Table schema:
+/// guild_id -> json encoded PersistedSession
+const SESSIONS_TABLE: TableDefinition<u64, &str> = TableDefinition::new("persisted_sessions");
+
+/// Session which was active when saved, restored after restart.
+///
+/// Fields added later must have a default, so that sessions saved by older versions keep loading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedSession {
+    pub guild: GuildId,
+    pub voice: ChannelId,
+    pub text: ChannelId,
+    #[serde(default)]
+    pub extra_text: Vec<ChannelId>,
+    #[serde(default)]
+    pub mode: ReadingMode,
+    #[serde(default)]
+    pub followed: Option<UserId>,
+}
+
+pub struct SessionRepository {
+    db: Arc<Database>,
+}
+
+impl SessionRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_all(&self) -> anyhow::Result<Vec<PersistedSession>> {
+        let db = self.db.clone();
+
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<PersistedSession>> {
+            let tx = db.begin_read()?;
+            let table = match tx.open_table(SESSIONS_TABLE) {
+                Ok(table) => table,
+                Err(_) => return Ok(Vec::new()),
+            };
+
+            table
+                .iter()?
+                .map(|entry| {
+                    let (_, session) = entry?;
+                    Ok(serde_json::from_str(session.value())?)
+                })
+                .collect()
+        })
+        .await?
+    }
+
+    pub async fn save(&self, session: PersistedSession) -> anyhow::Result<()> {
+        let db = self.db.clone();
+
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let tx = db.begin_write()?;
+            {
+                let mut table = tx.open_table(SESSIONS_TABLE)?;
+                table.insert(
+                    session.guild.get(),
+                    serde_json::to_string(&session)?.as_str(),
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, guild: GuildId) -> anyhow::Result<()> {
+        let db = self.db.clone();
+
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let tx = db.begin_write()?;
+            {
+                let mut table = tx.open_table(SESSIONS_TABLE)?;
+                table.remove(guild.get())?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await??;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redb::backends::InMemoryBackend;
+
+    fn create_repository() -> SessionRepository {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .expect("must create in-memory database");
+        SessionRepository::new(Arc::new(db))
+    }
+
+    fn session(guild: u64) -> PersistedSession {
+        PersistedSession {
+            guild: GuildId::new(guild),
+            voice: ChannelId::new(guild * 10),
+            text: ChannelId::new(guild * 10 + 1),
+            extra_text: Vec::new(),
+            mode: ReadingMode::default(),
+            followed: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn find_all_is_empty_before_any_save() {
+        let repository = create_repository();
+
+        assert!(repository.find_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn saved_sessions_are_listed_until_deleted() {
+        let repository = create_repository();
+        repository.save(session(1)).await.unwrap();
+        repository.save(session(2)).await.unwrap();
+
+        repository.delete(GuildId::new(1)).await.unwrap();
+
+        assert_eq!(repository.find_all().await.unwrap(), vec![session(2)]);
+    }
+
+    #[tokio::test]
+    async fn reading_mode_is_restored() {
+        let repository = create_repository();
+        let saved = PersistedSession {
+            mode: ReadingMode::VoiceChat,
+            ..session(1)
+        };
+        repository.save(saved.clone()).await.unwrap();
+
+        assert_eq!(repository.find_all().await.unwrap(), vec![saved]);
+    }
+
+    #[tokio::test]
+    async fn extra_text_channels_and_followed_user_are_restored() {
+        let repository = create_repository();
+        let saved = PersistedSession {
+            extra_text: vec![ChannelId::new(12), ChannelId::new(13)],
+            followed: Some(UserId::new(100)),
+            ..session(1)
+        };
+        repository.save(saved.clone()).await.unwrap();
+
+        assert_eq!(repository.find_all().await.unwrap(), vec![saved]);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::session::event::SessionEvent;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Posts every session event as JSON to an outbound webhook.
+pub struct WebhookSink {
+    http: reqwest::Client,
+    url: reqwest::Url,
+}
+
+impl WebhookSink {
+    pub fn new(http: reqwest::Client, url: reqwest::Url) -> Self {
+        Self { http, url }
+    }
+
+    pub async fn run(self, mut rx: broadcast::Receiver<SessionEvent>) {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Err(e) = self.post(&event).await {
+                        tracing::warn!("Failed to post event {:?} to webhook: {:?}", event, e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Webhook fell behind, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn post(&self, event: &SessionEvent) -> anyhow::Result<()> {
+        self.http
+            .post(self.url.clone())
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
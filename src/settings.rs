@@ -0,0 +1,267 @@
+use crate::session::{NameAnnouncement, SpeakerNamePolicy};
+use poise::serenity_prelude::{GuildId, RoleId};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Table schema:
+/// guild_id -> json encoded GuildSettings
+const GUILD_SETTINGS_TABLE: TableDefinition<u64, &str> = TableDefinition::new("guild_settings");
+
+/// Per-guild settings which survive across sessions.
+///
+/// Every field must have a default, so that settings saved by older versions keep loading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Playback volume in percent.
+    pub volume: u16,
+    /// Whether to say the channel name of messages read from channels added with `add-channel`.
+    pub announce_channel_name: bool,
+    /// Whether to say the author of the message replied to.
+    pub announce_reply: bool,
+    /// When to read the name of the speaker.
+    pub speaker_name: SpeakerNamePolicy,
+    /// Seconds of silence after which the name is read again, for [`SpeakerNamePolicy::OnChangeOrSilence`].
+    pub speaker_name_silence_secs: u64,
+    /// Minutes without any message before leaving the voice channel. `0` disables it.
+    pub idle_timeout_minutes: u64,
+    /// What is described besides the text of a message.
+    pub descriptions: DescriptionSettings,
+    /// Who gets announced when joining or leaving the voice channel.
+    pub voice_notifications: VoiceNotificationSettings,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            volume: 100,
+            announce_channel_name: false,
+            announce_reply: false,
+            speaker_name: SpeakerNamePolicy::default(),
+            speaker_name_silence_secs: 30,
+            idle_timeout_minutes: 0,
+            descriptions: DescriptionSettings::default(),
+            voice_notifications: VoiceNotificationSettings::default(),
+        }
+    }
+}
+
+impl GuildSettings {
+    pub fn volume_ratio(&self) -> f32 {
+        self.volume as f32 / 100.0
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_minutes > 0).then(|| Duration::from_secs(self.idle_timeout_minutes * 60))
+    }
+
+    pub fn name_announcement(&self) -> NameAnnouncement {
+        NameAnnouncement {
+            policy: self.speaker_name,
+            silence: Duration::from_secs(self.speaker_name_silence_secs),
+        }
+    }
+}
+
+/// Whether each kind of content is described when a message is read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DescriptionSettings {
+    pub attachments: bool,
+    pub stickers: bool,
+    pub polls: bool,
+    /// off by default, since link previews would be read after every URL
+    pub embeds: bool,
+}
+
+impl Default for DescriptionSettings {
+    fn default() -> Self {
+        Self {
+            attachments: true,
+            stickers: true,
+            polls: true,
+            embeds: false,
+        }
+    }
+}
+
+impl DescriptionSettings {
+    pub fn set(&mut self, category: DescriptionCategory, enabled: bool) {
+        let field = match category {
+            DescriptionCategory::Attachments => &mut self.attachments,
+            DescriptionCategory::Stickers => &mut self.stickers,
+            DescriptionCategory::Polls => &mut self.polls,
+            DescriptionCategory::Embeds => &mut self.embeds,
+        };
+        *field = enabled;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum DescriptionCategory {
+    #[name = "attachments"]
+    Attachments,
+    #[name = "stickers"]
+    Stickers,
+    #[name = "polls"]
+    Polls,
+    #[name = "embeds"]
+    Embeds,
+}
+
+/// Which joins and leaves of the voice channel are announced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceNotificationSettings {
+    pub enabled: bool,
+    pub ignore_bots: bool,
+    /// Seconds a leave is held back, so that leaving and rejoining is not announced. `0` disables it.
+    pub debounce_secs: u64,
+    /// Only members with any of these roles are announced. Empty means everyone.
+    pub roles: Vec<RoleId>,
+}
+
+impl Default for VoiceNotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ignore_bots: false,
+            debounce_secs: 0,
+            roles: Vec::new(),
+        }
+    }
+}
+
+impl VoiceNotificationSettings {
+    /// Whether joins and leaves of a member are announced.
+    pub fn announces(&self, bot: bool, roles: &[RoleId]) -> bool {
+        self.enabled
+            && !(bot && self.ignore_bots)
+            && (self.roles.is_empty() || self.roles.iter().any(|role| roles.contains(role)))
+    }
+
+    pub fn debounce(&self) -> Option<Duration> {
+        (self.debounce_secs > 0).then(|| Duration::from_secs(self.debounce_secs))
+    }
+
+    pub fn set_role(&mut self, role: RoleId, enabled: bool) {
+        self.roles.retain(|r| *r != role);
+        if enabled {
+            self.roles.push(role);
+        }
+    }
+}
+
+pub struct GuildSettingsRepository {
+    db: Arc<Database>,
+}
+
+impl GuildSettingsRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Returns settings of the guild, or defaults if nothing has been saved.
+    pub async fn find(&self, guild: GuildId) -> anyhow::Result<GuildSettings> {
+        let tx = self.db.begin_read()?;
+        let table = match tx.open_table(GUILD_SETTINGS_TABLE) {
+            Ok(table) => table,
+            Err(_) => return Ok(GuildSettings::default()),
+        };
+
+        match table.get(guild.get())? {
+            Some(settings) => Ok(serde_json::from_str(settings.value())?),
+            None => Ok(GuildSettings::default()),
+        }
+    }
+
+    /// Applies `f` to the settings of the guild in a single write transaction.
+    pub async fn update<F>(&self, guild: GuildId, f: F) -> anyhow::Result<GuildSettings>
+    where
+        F: FnOnce(&mut GuildSettings) + Send + 'static,
+    {
+        let db = self.db.clone();
+
+        let settings = tokio::task::spawn_blocking(move || -> anyhow::Result<GuildSettings> {
+            let tx = db.begin_write()?;
+            let settings = {
+                let mut table = tx.open_table(GUILD_SETTINGS_TABLE)?;
+                let mut settings = table
+                    .get(guild.get())?
+                    .map(|settings| serde_json::from_str(settings.value()))
+                    .transpose()?
+                    .unwrap_or_default();
+                f(&mut settings);
+                table.insert(guild.get(), serde_json::to_string(&settings)?.as_str())?;
+                settings
+            };
+            tx.commit()?;
+            Ok(settings)
+        })
+        .await??;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redb::backends::InMemoryBackend;
+
+    fn create_repository() -> GuildSettingsRepository {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .expect("must create in-memory database");
+        GuildSettingsRepository::new(Arc::new(db))
+    }
+
+    #[tokio::test]
+    async fn returns_default_if_nothing_saved() {
+        let repository = create_repository();
+
+        let settings = repository.find(GuildId::new(1)).await.unwrap();
+
+        assert_eq!(settings, GuildSettings::default());
+    }
+
+    #[tokio::test]
+    async fn update_persists_changes_per_guild() {
+        let repository = create_repository();
+
+        repository
+            .update(GuildId::new(1), |settings| settings.volume = 50)
+            .await
+            .unwrap();
+
+        assert_eq!(repository.find(GuildId::new(1)).await.unwrap().volume, 50);
+        assert_eq!(repository.find(GuildId::new(2)).await.unwrap().volume, 100);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_default() {
+        let settings: GuildSettings = serde_json::from_str("{}").unwrap();
+
+        assert_eq!(settings, GuildSettings::default());
+    }
+
+    #[test]
+    fn voice_notifications_filter_bots_and_roles() {
+        let mut settings = VoiceNotificationSettings::default();
+        assert!(settings.announces(true, &[]));
+
+        settings.ignore_bots = true;
+        settings.set_role(RoleId::new(1), true);
+        assert!(!settings.announces(true, &[RoleId::new(1)]));
+        assert!(!settings.announces(false, &[RoleId::new(2)]));
+        assert!(settings.announces(false, &[RoleId::new(2), RoleId::new(1)]));
+
+        settings.set_role(RoleId::new(1), false);
+        assert!(settings.announces(false, &[]));
+
+        settings.enabled = false;
+        assert!(!settings.announces(false, &[]));
+    }
+}
@@ -1,5 +1,5 @@
 use poise::serenity_prelude as serenity;
-use poise::serenity_prelude::{ChannelMention, GuildId, Mentionable, RoleId, User};
+use poise::serenity_prelude::{ChannelMention, Guild, GuildId, Mentionable, RoleId, User};
 use regex::Regex;
 use std::sync::LazyLock;
 use tracing::warn;
@@ -28,11 +28,7 @@ pub fn normalize_mentions(
     for user in users {
         content = content.replace(
             user.mention().to_string().as_str(),
-            guild
-                .members
-                .get(&user.id)
-                .map(|member| member.display_name())
-                .unwrap_or(user.display_name()),
+            display_name(&guild, user),
         );
     }
     for role_id in roles {
@@ -55,6 +51,22 @@ pub fn normalize_mentions(
     content
 }
 
+/// Name of the user as shown in the guild, resolved the same way as mentions.
+pub fn member_display_name(ctx: &serenity::Context, guild_id: GuildId, user: &User) -> String {
+    match guild_id.to_guild_cached(&ctx.cache) {
+        Some(guild) => display_name(&guild, user).to_string(),
+        None => user.display_name().to_string(),
+    }
+}
+
+fn display_name<'a>(guild: &'a Guild, user: &'a User) -> &'a str {
+    guild
+        .members
+        .get(&user.id)
+        .map(|member| member.display_name())
+        .unwrap_or(user.display_name())
+}
+
 pub fn normalize_urls(content: &str) -> String {
     URL_REGEX.replace_all(content, "URL").to_string()
 }
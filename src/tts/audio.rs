@@ -0,0 +1,87 @@
+//! Helpers for backends which produce headerless PCM.
+
+/// Resamples mono audio by linear interpolation.
+pub fn resample(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let len = (samples.len() as u64 * to as u64).div_ceil(from as u64) as usize;
+    let step = from as f64 / to as f64;
+
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let current = samples[index.min(samples.len() - 1)] as f64;
+            let next = samples[(index + 1).min(samples.len() - 1)] as f64;
+            let sample = current + (next - current) * position.fract();
+            sample.round() as i16
+        })
+        .collect()
+}
+
+/// Encodes mono 16-bit PCM as a WAV file, so that it can be probed for playback.
+pub fn wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    const CHANNELS: u16 = 1;
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    let data_len = (samples.len() * block_align as usize) as u32;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&CHANNELS.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+/// Decodes little-endian 16-bit PCM, ignoring a trailing odd byte.
+pub fn samples_from_le_bytes(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resample_interpolates_between_samples() {
+        assert_eq!(resample(&[0, 100], 1, 2), vec![0, 50, 100, 100]);
+        assert_eq!(resample(&[0, 50, 100, 100], 2, 1), vec![0, 100]);
+        assert_eq!(resample(&[1, 2, 3], 48_000, 48_000), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn resampled_length_follows_rate() {
+        let samples = vec![0; 22_050];
+
+        assert_eq!(resample(&samples, 22_050, 48_000).len(), 48_000);
+    }
+
+    #[test]
+    fn wav_header_describes_samples() {
+        let wav = wav(&[1, -1], 48_000);
+
+        assert_eq!(wav.len(), 48);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 48_000);
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 4);
+        assert_eq!(samples_from_le_bytes(&wav[44..]), vec![1, -1]);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::tts::{Voice, VoiceDetail, VoiceError, process};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const TEXT_PLACEHOLDER: &str = "{text}";
+const LANGUAGE_PLACEHOLDER: &str = "{lang}";
+
+/// Voice of a local program writing audio such as WAV to stdout, e.g. `espeak-ng -v {lang} --stdout -- {text}`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommandVoiceConfig {
+    /// Executable, looked up in `PATH` if not absolute. It is run directly, never through a shell.
+    pub program: PathBuf,
+    /// Arguments, each of which has `{text}` and `{lang}` replaced.
+    /// The text is written to stdin instead when no argument contains `{text}`.
+    ///
+    /// Messages starting with `-` would be taken as options, so an argument starting with `{text}`
+    /// must come after `--`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Substituted for `{lang}`.
+    #[serde(default = "crate::tts::default_language")]
+    pub language: String,
+    /// Seconds to wait for the program, overriding `[backend.command]`.
+    pub timeout: Option<u64>,
+    pub provider: Option<String>,
+}
+
+impl CommandVoiceConfig {
+    pub fn generate_default_detail(&self, key: &str) -> VoiceDetail {
+        let provider = self.provider.clone().unwrap_or_else(|| {
+            self.program
+                .file_name()
+                .unwrap_or(self.program.as_os_str())
+                .to_string_lossy()
+                .into_owned()
+        });
+        VoiceDetail {
+            name: key.to_string(),
+            provider,
+            description: None,
+        }
+    }
+
+    /// Rejects arguments where a message could be taken as an option.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let end_of_options = self.args.iter().position(|arg| arg == "--");
+        let text_first = self.args.iter().position(|arg| {
+            arg.replace(LANGUAGE_PLACEHOLDER, &self.language)
+                .starts_with(TEXT_PLACEHOLDER)
+        });
+        match (text_first, end_of_options) {
+            (Some(text), Some(end)) if end < text => Ok(()),
+            (Some(_), _) => Err(anyhow!(
+                "{} must come after `--` in the arguments of {}",
+                TEXT_PLACEHOLDER,
+                self.program.display()
+            )),
+            (None, _) => Ok(()),
+        }
+    }
+
+    fn reads_stdin(&self) -> bool {
+        !self.args.iter().any(|arg| arg.contains(TEXT_PLACEHOLDER))
+    }
+
+    /// Arguments with the placeholders replaced, each staying a single argument whatever the text contains.
+    fn args(&self, text: &str) -> Vec<String> {
+        // an argument cannot hold NUL, which is never spoken anyway
+        let text = text.replace('\0', "");
+        self.args
+            .iter()
+            .map(|arg| {
+                arg.replace(LANGUAGE_PLACEHOLDER, &self.language)
+                    .replace(TEXT_PLACEHOLDER, &text)
+            })
+            .collect()
+    }
+}
+
+pub struct CommandVoice {
+    identifier: String,
+    timeout: Duration,
+    config: CommandVoiceConfig,
+}
+
+impl CommandVoice {
+    /// `default_timeout` applies unless the profile sets its own.
+    pub fn new(config: CommandVoiceConfig, default_timeout: Duration) -> CommandVoice {
+        Self {
+            identifier: Self::build_identifier(&config),
+            timeout: config
+                .timeout
+                .map(Duration::from_secs)
+                .unwrap_or(default_timeout),
+            config,
+        }
+    }
+
+    fn build_identifier(config: &CommandVoiceConfig) -> String {
+        format!(
+            "command-({})",
+            serde_json::to_string(config).expect("failed to build identifier")
+        )
+    }
+
+    async fn run(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+        let input = if self.config.reads_stdin() { text } else { "" };
+        let audio = process::run(
+            &self.config.program,
+            self.config.args(text),
+            input.as_bytes(),
+            self.timeout,
+        )
+        .await?;
+
+        if audio.is_empty() {
+            return Err(anyhow!(
+                "{} wrote no audio to stdout",
+                self.config.program.display()
+            ));
+        }
+        Ok(audio)
+    }
+}
+
+#[async_trait]
+impl Voice for CommandVoice {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn language(&self) -> &str {
+        &self.config.language
+    }
+
+    async fn generate(&self, text: &str) -> Result<Vec<u8>, VoiceError> {
+        self.run(text).await.map_err(VoiceError::Api)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(program: &str, args: &[&str]) -> CommandVoice {
+        let config = CommandVoiceConfig {
+            program: PathBuf::from(program),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            language: "en".to_string(),
+            timeout: None,
+            provider: None,
+        };
+        CommandVoice::new(config, Duration::from_secs(5))
+    }
+
+    #[tokio::test]
+    async fn text_is_a_literal_argument() {
+        let voice = voice("printf", &["%s|%s", "{lang}", "{text}"]);
+        let text = "'; rm -rf ~ && $(id) `id` | cat > /tmp/x {lang}";
+
+        let audio = voice.generate(text).await.unwrap();
+
+        assert_eq!(String::from_utf8(audio).unwrap(), format!("en|{}", text));
+    }
+
+    #[tokio::test]
+    async fn text_after_end_of_options_is_not_modified() {
+        let voice = voice("printf", &["--", "%s|%s", "{text}", "--text={text}"]);
+        voice.config.verify().unwrap();
+
+        let audio = voice.generate("-n hello\0").await.unwrap();
+
+        assert_eq!(audio, b"-n hello|--text=-n hello");
+    }
+
+    #[test]
+    fn text_which_could_be_an_option_is_rejected() {
+        for args in [
+            &["-v", "{lang}", "--stdout", "{text}"][..],
+            &["{text}", "--"],
+            &["{text}.wav"],
+        ] {
+            assert!(
+                voice("espeak-ng", args).config.verify().is_err(),
+                "{:?}",
+                args
+            );
+        }
+
+        for args in [
+            &["-v", "{lang}", "--stdout", "--", "{text}"][..],
+            &["--text={text}"],
+            &["--stdout"],
+        ] {
+            voice("espeak-ng", args).config.verify().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn text_is_written_to_stdin_without_placeholder() {
+        let voice = voice("cat", &[]);
+
+        let audio = voice.generate("hello $HOME").await.unwrap();
+
+        assert_eq!(audio, b"hello $HOME");
+    }
+
+    #[tokio::test]
+    async fn failures_are_api_errors() {
+        let timeout = CommandVoice::new(voice("sleep", &["5"]).config, Duration::from_millis(100));
+
+        for voice in [voice("false", &[]), voice("printf", &[""]), timeout] {
+            assert!(
+                matches!(voice.generate("hello").await, Err(VoiceError::Api(_))),
+                "{:?}",
+                voice.config
+            );
+        }
+    }
+}
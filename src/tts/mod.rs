@@ -1,6 +1,12 @@
+mod audio;
 mod cache;
+pub mod command;
 pub mod google_cloud;
+pub mod openai;
+pub mod piper;
+mod process;
 pub mod registry;
+pub mod scheduler;
 pub mod voicevox;
 
 use async_trait::async_trait;
@@ -9,6 +15,11 @@ use thiserror::Error;
 
 const DISCORD_SAMPLE_RATE: i32 = 48_000;
 
+/// Language of voices unless configured, used for messages such as the name of the speaker.
+fn default_language() -> String {
+    "en-US".to_string()
+}
+
 #[derive(Debug, Error)]
 pub enum VoiceError {
     #[error("API request failed: {0}")]
@@ -87,4 +98,103 @@ pub mod test_utils {
             Ok(text.as_bytes().to_vec())
         }
     }
+
+    /// HTTP server answering a single request with a fixed response, recording the request.
+    pub struct StandInServer {
+        url: String,
+        request: tokio::task::JoinHandle<RecordedRequest>,
+    }
+
+    pub struct RecordedRequest {
+        pub method: String,
+        pub path: String,
+        pub headers: Vec<(String, String)>,
+        pub body: Vec<u8>,
+    }
+
+    impl RecordedRequest {
+        pub fn header(&self, name: &str) -> Option<String> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    impl StandInServer {
+        pub async fn start(body: Vec<u8>) -> Self {
+            Self::start_with_status(200, body).await
+        }
+
+        pub async fn start_with_status(status: u16, body: Vec<u8>) -> Self {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+
+            let request = tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                let mut received = Vec::new();
+                let header_end = loop {
+                    let mut chunk = [0; 1024];
+                    let read = stream.read(&mut chunk).await.unwrap();
+                    assert!(read > 0, "connection closed before headers ended");
+                    received.extend_from_slice(&chunk[..read]);
+                    if let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break end;
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&received[..header_end]).to_string();
+                let mut lines = head.lines();
+                let mut request_line = lines.next().unwrap().split_whitespace();
+                let method = request_line.next().unwrap().to_string();
+                let path = request_line.next().unwrap().to_string();
+                let headers: Vec<(String, String)> = lines
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .collect();
+
+                let content_length = headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .map(|(_, value)| value.parse::<usize>().unwrap())
+                    .unwrap_or(0);
+                let mut request_body = received[header_end + 4..].to_vec();
+                while request_body.len() < content_length {
+                    let mut chunk = [0; 1024];
+                    let read = stream.read(&mut chunk).await.unwrap();
+                    assert!(read > 0, "connection closed before body ended");
+                    request_body.extend_from_slice(&chunk[..read]);
+                }
+
+                let head = format!(
+                    "HTTP/1.1 {} Stand-in\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+                stream.shutdown().await.unwrap();
+
+                RecordedRequest {
+                    method,
+                    path,
+                    headers,
+                    body: request_body,
+                }
+            });
+
+            Self { url, request }
+        }
+
+        pub fn url(&self) -> String {
+            self.url.clone()
+        }
+
+        pub async fn request(self) -> RecordedRequest {
+            self.request.await.unwrap()
+        }
+    }
 }
@@ -0,0 +1,189 @@
+use crate::tts::{Voice, VoiceDetail, VoiceError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    #[default]
+    Wav,
+    Mp3,
+    Flac,
+    Aac,
+    // Opus, // omit since it cannot be decoded for playback.
+    // Pcm, // omit since no header format requires effort to support.
+}
+
+#[derive(Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speed: Option<f32>,
+    response_format: ResponseFormat,
+}
+
+/// minimum client for `/audio/speech` of OpenAI-compatible engines
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl Client {
+    /// `base_url` is the API root which usually ends with `/v1`, such as `http://localhost:8880/v1`.
+    pub fn new(http: reqwest::Client, base_url: &str, api_key: Option<String>) -> Client {
+        Client {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    async fn speech(&self, request: &SpeechRequest<'_>) -> anyhow::Result<Vec<u8>> {
+        let mut builder = self
+            .http
+            .post(format!("{}/audio/speech", self.base_url))
+            .json(request);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+
+        let res = builder.send().await?.error_for_status()?;
+
+        Ok(res.bytes().await?.to_vec())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpenAiSpeechVoiceConfig {
+    /// Name of the engine under `[backend.openai_compatible]`.
+    pub engine: String,
+    pub model: String,
+    pub voice: String,
+    #[serde(default = "crate::tts::default_language")]
+    pub language: String,
+    pub speed: Option<f32>,
+    pub response_format: Option<ResponseFormat>,
+}
+
+impl OpenAiSpeechVoiceConfig {
+    pub fn generate_default_detail(&self, key: &str) -> VoiceDetail {
+        VoiceDetail {
+            name: key.to_string(),
+            provider: self.engine.clone(),
+            description: None,
+        }
+    }
+}
+
+pub struct OpenAiSpeechVoice {
+    identifier: String,
+    client: Client,
+    config: OpenAiSpeechVoiceConfig,
+}
+
+impl OpenAiSpeechVoice {
+    pub fn new(client: Client, config: OpenAiSpeechVoiceConfig) -> OpenAiSpeechVoice {
+        let identifier = Self::build_identifier(&config);
+        Self {
+            identifier,
+            client,
+            config,
+        }
+    }
+
+    fn build_identifier(config: &OpenAiSpeechVoiceConfig) -> String {
+        format!(
+            "openai_compatible-({})",
+            serde_json::to_string(config).expect("failed to build identifier")
+        )
+    }
+}
+
+#[async_trait]
+impl Voice for OpenAiSpeechVoice {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn language(&self) -> &str {
+        &self.config.language
+    }
+
+    async fn generate(&self, text: &str) -> Result<Vec<u8>, VoiceError> {
+        let request = SpeechRequest {
+            model: &self.config.model,
+            input: text,
+            voice: &self.config.voice,
+            speed: self.config.speed,
+            response_format: self.config.response_format.unwrap_or_default(),
+        };
+
+        self.client.speech(&request).await.map_err(VoiceError::Api)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tts::test_utils::StandInServer;
+    use serde_json::json;
+
+    fn config() -> OpenAiSpeechVoiceConfig {
+        OpenAiSpeechVoiceConfig {
+            engine: "kokoro".to_string(),
+            model: "kokoro".to_string(),
+            voice: "af_heart".to_string(),
+            language: crate::tts::default_language(),
+            speed: Some(1.25),
+            response_format: Some(ResponseFormat::Mp3),
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_speech_request_and_returns_audio() {
+        let server = StandInServer::start(b"audio".to_vec()).await;
+        let client = Client::new(
+            reqwest::Client::new(),
+            &format!("{}/v1/", server.url()),
+            Some("secret".to_string()),
+        );
+        let voice = OpenAiSpeechVoice::new(client, config());
+
+        let audio = voice.generate("hello").await.unwrap();
+
+        assert_eq!(audio, b"audio");
+        let request = server.request().await;
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/audio/speech");
+        assert_eq!(
+            request.header("authorization").as_deref(),
+            Some("Bearer secret")
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&request.body).unwrap(),
+            json!({
+                "model": "kokoro",
+                "input": "hello",
+                "voice": "af_heart",
+                "speed": 1.25,
+                "response_format": "mp3",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn error_status_is_api_error() {
+        let server = StandInServer::start_with_status(500, Vec::new()).await;
+        let client = Client::new(reqwest::Client::new(), &server.url(), None);
+        let voice = OpenAiSpeechVoice::new(client, config());
+
+        assert!(matches!(
+            voice.generate("hello").await,
+            Err(VoiceError::Api(_))
+        ));
+    }
+}
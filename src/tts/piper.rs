@@ -0,0 +1,249 @@
+use crate::tts::audio::{resample, samples_from_le_bytes, wav};
+use crate::tts::process;
+use crate::tts::{DISCORD_SAMPLE_RATE, Voice, VoiceDetail, VoiceError};
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How the piper executable is run, shared by every Piper voice.
+#[derive(Clone, Debug)]
+pub struct Runner {
+    binary: PathBuf,
+    timeout: Duration,
+}
+
+impl Runner {
+    pub fn new(binary: PathBuf, timeout: Duration) -> Runner {
+        Runner { binary, timeout }
+    }
+
+    /// Synthesizes `text` into raw 16-bit mono PCM at the sample rate of the model.
+    async fn synthesize(&self, args: &[String], text: &str) -> anyhow::Result<Vec<u8>> {
+        // piper synthesizes each line separately, so the message is read as a single line
+        let line = format!("{}\n", text.replace(['\r', '\n'], " "));
+        process::run(&self.binary, args, line.as_bytes(), self.timeout).await
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PiperVoiceConfig {
+    /// Path of the `.onnx` voice, with its `.onnx.json` config next to it.
+    pub model: PathBuf,
+    pub speaker_id: Option<u32>,
+    pub length_scale: Option<f32>,
+    pub noise_scale: Option<f32>,
+    pub noise_w: Option<f32>,
+    #[serde(default = "crate::tts::default_language")]
+    pub language: String,
+}
+
+impl PiperVoiceConfig {
+    pub fn generate_default_detail(&self, key: &str) -> VoiceDetail {
+        VoiceDetail {
+            name: key.to_string(),
+            provider: "Piper".to_string(),
+            description: None,
+        }
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--model".to_string(),
+            self.model.display().to_string(),
+            "--output-raw".to_string(),
+        ];
+        let options = [
+            ("--speaker", self.speaker_id.map(|id| id.to_string())),
+            ("--length_scale", self.length_scale.map(|v| v.to_string())),
+            ("--noise_scale", self.noise_scale.map(|v| v.to_string())),
+            ("--noise_w", self.noise_w.map(|v| v.to_string())),
+        ];
+        for (flag, value) in options {
+            if let Some(value) = value {
+                args.push(flag.to_string());
+                args.push(value);
+            }
+        }
+        args
+    }
+}
+
+#[derive(Deserialize)]
+struct ModelConfig {
+    audio: ModelAudioConfig,
+}
+
+#[derive(Deserialize)]
+struct ModelAudioConfig {
+    sample_rate: u32,
+}
+
+/// Reads the output sample rate from the `.onnx.json` config of the model.
+fn model_sample_rate(model: &Path) -> anyhow::Result<u32> {
+    let mut path = model.as_os_str().to_owned();
+    path.push(".json");
+    let path = PathBuf::from(path);
+
+    let json = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read piper model config {}", path.display()))?;
+    let config: ModelConfig = serde_json::from_str(&json)
+        .with_context(|| format!("Invalid piper model config {}", path.display()))?;
+
+    Ok(config.audio.sample_rate)
+}
+
+pub struct PiperVoice {
+    identifier: String,
+    runner: Runner,
+    args: Vec<String>,
+    sample_rate: u32,
+    config: PiperVoiceConfig,
+}
+
+impl PiperVoice {
+    pub fn new(runner: Runner, config: PiperVoiceConfig) -> anyhow::Result<PiperVoice> {
+        let sample_rate = model_sample_rate(&config.model)?;
+        Ok(Self {
+            identifier: Self::build_identifier(&config),
+            runner,
+            args: config.args(),
+            sample_rate,
+            config,
+        })
+    }
+
+    fn build_identifier(config: &PiperVoiceConfig) -> String {
+        format!(
+            "piper-({})",
+            serde_json::to_string(config).expect("failed to build identifier")
+        )
+    }
+}
+
+#[async_trait]
+impl Voice for PiperVoice {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn language(&self) -> &str {
+        &self.config.language
+    }
+
+    async fn generate(&self, text: &str) -> Result<Vec<u8>, VoiceError> {
+        let raw = self
+            .runner
+            .synthesize(&self.args, text)
+            .await
+            .map_err(VoiceError::Api)?;
+
+        let samples = resample(
+            &samples_from_le_bytes(&raw),
+            self.sample_rate,
+            DISCORD_SAMPLE_RATE as u32,
+        );
+
+        Ok(wav(&samples, DISCORD_SAMPLE_RATE as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Directory with a fake piper script and a model config sampled at 24kHz.
+    struct FakePiper {
+        dir: PathBuf,
+    }
+
+    impl FakePiper {
+        fn new(name: &str, script: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("piper-test-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let binary = dir.join("piper");
+            std::fs::write(&binary, format!("#!/bin/sh\n{}", script)).unwrap();
+            std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+            std::fs::write(
+                dir.join("voice.onnx.json"),
+                r#"{"audio": {"sample_rate": 24000}}"#,
+            )
+            .unwrap();
+
+            Self { dir }
+        }
+
+        fn voice(&self) -> PiperVoice {
+            let runner = Runner::new(self.dir.join("piper"), Duration::from_secs(5));
+            let config = PiperVoiceConfig {
+                model: self.dir.join("voice.onnx"),
+                speaker_id: Some(3),
+                length_scale: None,
+                noise_scale: None,
+                noise_w: None,
+                language: crate::tts::default_language(),
+            };
+            PiperVoice::new(runner, config).unwrap()
+        }
+    }
+
+    impl Drop for FakePiper {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[tokio::test]
+    async fn output_is_resampled_into_wav() {
+        // two samples at 24kHz, after recording the arguments and the text
+        let piper = FakePiper::new(
+            "resample",
+            r#"echo "$@" > "$(dirname "$0")/args"; cat > "$(dirname "$0")/text"; printf '\000\000\000\020'"#,
+        );
+
+        let audio = piper.voice().generate("hello\nworld").await.unwrap();
+
+        assert_eq!(
+            u32::from_le_bytes(audio[24..28].try_into().unwrap()),
+            48_000
+        );
+        assert_eq!(
+            samples_from_le_bytes(&audio[44..]),
+            vec![0, 2048, 4096, 4096]
+        );
+        let args = std::fs::read_to_string(piper.dir.join("args")).unwrap();
+        assert!(args.contains("--output-raw --speaker 3"), "{}", args);
+        let text = std::fs::read_to_string(piper.dir.join("text")).unwrap();
+        assert_eq!(text, "hello world\n");
+    }
+
+    #[tokio::test]
+    async fn failure_is_api_error_with_stderr() {
+        let piper = FakePiper::new("failure", "echo 'model not found' >&2; exit 1");
+
+        let Err(VoiceError::Api(err)) = piper.voice().generate("hello").await else {
+            panic!("failure must be an API error");
+        };
+
+        assert!(err.to_string().contains("model not found"), "{}", err);
+    }
+
+    #[test]
+    fn missing_model_config_is_reported() {
+        let runner = Runner::new(PathBuf::from("piper"), Duration::from_secs(5));
+        let config = PiperVoiceConfig {
+            model: PathBuf::from("/nonexistent/voice.onnx"),
+            speaker_id: None,
+            length_scale: None,
+            noise_scale: None,
+            noise_w: None,
+            language: crate::tts::default_language(),
+        };
+
+        assert!(PiperVoice::new(runner, config).is_err());
+    }
+}
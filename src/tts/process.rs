@@ -0,0 +1,89 @@
+use anyhow::{Context, anyhow};
+use std::ffi::OsStr;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Runs a program without any shell, feeding `input` to its stdin, and returns its stdout.
+///
+/// The program is killed if it does not finish within `timeout`.
+pub async fn run<I, S>(
+    program: impl AsRef<OsStr>,
+    args: I,
+    input: &[u8],
+    timeout: Duration,
+) -> anyhow::Result<Vec<u8>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let program = program.as_ref();
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program.display()))?;
+
+    let mut stdin = child.stdin.take().expect("stdin must be piped");
+    let write = async {
+        let written = stdin.write_all(input).await;
+        drop(stdin);
+        match written {
+            // exiting before reading is reported by the exit status, along with stderr
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => Err(err),
+            _ => Ok(()),
+        }
+    };
+
+    // the output is read while writing, so that neither side waits for the other to drain a pipe
+    let output = tokio::time::timeout(timeout, async {
+        let (written, output) = tokio::join!(write, child.wait_with_output());
+        written?;
+        output
+    })
+    .await
+    .map_err(|_| anyhow!("{} did not finish in {:?}", program.display(), timeout))??;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} exited with {}: {}",
+            program.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// More than a pipe buffer holds.
+    fn large_input() -> Vec<u8> {
+        vec![b'a'; 1 << 20]
+    }
+
+    #[tokio::test]
+    async fn large_input_and_output_do_not_block_each_other() {
+        let input = large_input();
+
+        let output = run("cat", [""; 0], &input, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[tokio::test]
+    async fn timeout_covers_unread_input() {
+        let result = run("sleep", ["5"], &large_input(), Duration::from_millis(100)).await;
+
+        assert!(result.unwrap_err().to_string().contains("did not finish"));
+    }
+}
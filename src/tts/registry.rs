@@ -1,13 +1,18 @@
-use crate::config::{AppConfig, CacheConfig, ProfileBackendConfig};
+use crate::config::{AppConfig, CacheConfig, DEFAULT_VOICEVOX_ENGINE, ProfileBackendConfig};
 use crate::tts::cache::CachedVoice;
+use crate::tts::command::CommandVoice;
 use crate::tts::google_cloud::GoogleCloudVoice;
-use crate::tts::voicevox::VoicevoxVoice;
-use crate::tts::{Voice, VoiceDetail, voicevox};
+use crate::tts::openai::OpenAiSpeechVoice;
+use crate::tts::piper::PiperVoice;
+use crate::tts::scheduler::{Limiter, ScheduledVoice};
+use crate::tts::voicevox::{VoicevoxVoice, VoicevoxVoiceConfig};
+use crate::tts::{Voice, VoiceDetail, openai, piper, voicevox};
 use anyhow::Context;
 use google_cloud_texttospeech_v1::client::TextToSpeech;
 use moka::future::Cache;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct VoicePackage {
     pub voice: Arc<dyn Voice>,
@@ -16,6 +21,22 @@ pub struct VoicePackage {
 }
 
 impl VoicePackage {
+    fn new(voice: Arc<dyn Voice>, detail: VoiceDetail) -> Self {
+        let search_index = format!(
+            "{} {} {}",
+            detail.name,
+            detail.provider,
+            detail.description.as_deref().unwrap_or("")
+        )
+        .to_lowercase();
+
+        Self {
+            voice,
+            detail,
+            search_index,
+        }
+    }
+
     fn matches_keywords(&self, keywords: &[String]) -> bool {
         keywords
             .iter()
@@ -71,7 +92,12 @@ pub struct VoiceRegistryBuilder {
     config: AppConfig,
     moka_cache: Option<Cache<String, Vec<u8>>>,
     google_cloud: Option<TextToSpeech>,
-    voicevox: Option<voicevox::Client>,
+    /// clients of VOICEVOX-compatible engines by name
+    voicevox: HashMap<String, voicevox::Client>,
+    /// speakers listed by engines which generate profiles, by engine name
+    voicevox_speakers: HashMap<String, Vec<voicevox::Speaker>>,
+    /// clients of OpenAI-compatible engines by name
+    openai_compatible: HashMap<String, openai::Client>,
 }
 
 impl VoiceRegistryBuilder {
@@ -85,7 +111,9 @@ impl VoiceRegistryBuilder {
             config,
             moka_cache,
             google_cloud: None,
-            voicevox: None,
+            voicevox: HashMap::new(),
+            voicevox_speakers: HashMap::new(),
+            openai_compatible: HashMap::new(),
         }
     }
 
@@ -94,20 +122,64 @@ impl VoiceRegistryBuilder {
         self
     }
 
-    pub fn voicevox(mut self, voicevox: voicevox::Client) -> Self {
-        self.voicevox = Some(voicevox);
+    /// Registers the client of the VOICEVOX-compatible engine configured under `name`.
+    pub fn voicevox(mut self, name: impl Into<String>, voicevox: voicevox::Client) -> Self {
+        self.voicevox.insert(name.into(), voicevox);
+        self
+    }
+
+    /// Generates a profile for every talk style of the engine, named `<engine>-<style id>`.
+    ///
+    /// Profiles in the config take precedence over generated ones for the same style.
+    pub fn voicevox_speakers(
+        mut self,
+        name: impl Into<String>,
+        speakers: Vec<voicevox::Speaker>,
+    ) -> Self {
+        self.voicevox_speakers.insert(name.into(), speakers);
+        self
+    }
+
+    /// Registers the client of the OpenAI-compatible engine configured under `name`.
+    pub fn openai_compatible(mut self, name: impl Into<String>, client: openai::Client) -> Self {
+        self.openai_compatible.insert(name.into(), client);
         self
     }
 
     pub fn build(self) -> anyhow::Result<VoicePackageRegistry> {
         let mut voices = HashMap::new();
 
+        // one limiter per backend, shared by every voice using it
+        let backend = &self.config.backend;
+        let google_cloud_limiter = backend
+            .google_cloud
+            .as_ref()
+            .map(|c| Limiter::new(c.concurrency_limit()));
+        let voicevox_engines = backend.voicevox_engines()?;
+        let voicevox_limiters: HashMap<_, _> = voicevox_engines
+            .iter()
+            .map(|(name, c)| (name.as_str(), Limiter::new(c.concurrency_limit())))
+            .collect();
+        let piper = backend.piper.as_ref().filter(|c| c.enabled).map(|c| {
+            (
+                piper::Runner::new(c.binary.clone(), Duration::from_secs(c.timeout)),
+                Limiter::new(c.concurrency_limit()),
+            )
+        });
+        let command = backend.command.as_ref().filter(|c| c.enabled).map(|c| {
+            (
+                Duration::from_secs(c.timeout),
+                Limiter::new(c.concurrency_limit()),
+            )
+        });
+        let openai_limiters: HashMap<_, _> = backend
+            .openai_compatible
+            .iter()
+            .map(|(name, c)| (name.as_str(), Limiter::new(c.concurrency_limit())))
+            .collect();
+
         for (id, profile) in &self.config.profiles {
-            let detail = profile
-                .note
-                .as_ref()
-                .map(|config| config.resolve(profile.voice_backend.generate_default_detail(id)))
-                .unwrap_or_else(|| profile.voice_backend.generate_default_detail(id));
+            let mut default_detail = profile.voice_backend.generate_default_detail(id);
 
             let voice: Arc<dyn Voice> = match &profile.voice_backend {
                 ProfileBackendConfig::GoogleCloudVoice(c) => {
@@ -118,41 +190,147 @@ impl VoiceRegistryBuilder {
                         ))?
                         .clone();
 
-                    self.wrap_with_cache(Box::new(GoogleCloudVoice::new(client, c.clone())))
+                    self.wrap_with_cache(Self::schedule(
+                        Box::new(GoogleCloudVoice::new(client, c.clone())),
+                        &google_cloud_limiter,
+                    ))
                 }
                 ProfileBackendConfig::VoicevoxVoice(c) => {
-                    let client = self.voicevox.as_ref()
+                    let name = c.engine.as_deref().unwrap_or(DEFAULT_VOICEVOX_ENGINE);
+                    let (engine, client) = voicevox_engines.get(name)
+                        .zip(self.voicevox.get(name))
+                        .with_context(|| format!(
+                            "Preset '{}' requires the VoiceVox engine '{}', but it is not configured. Please verify that [backend.voicevox] (or [backend.voicevox_compatible.{}]) exists with 'enabled = true' and a valid 'url' in config.toml.",
+                            id, name, name
+                        ))?;
+
+                    default_detail.provider = engine.provider(name);
+
+                    self.wrap_with_cache(Self::schedule(
+                        Box::new(VoicevoxVoice::new(
+                            client.clone(),
+                            c.clone(),
+                            engine.language.clone(),
+                        )),
+                        &voicevox_limiters.get(name).cloned(),
+                    ))
+                }
+                ProfileBackendConfig::OpenAiSpeechVoice(c) => {
+                    let name = c.engine.as_str();
+                    let (engine, client) = backend.openai_compatible.get(name)
+                        .filter(|engine| engine.enabled)
+                        .zip(self.openai_compatible.get(name))
+                        .with_context(|| format!(
+                            "Preset '{}' requires the OpenAI-compatible engine '{}', but it is not configured. Please verify that [backend.openai_compatible.{}] exists with a valid 'url' in config.toml.",
+                            id, name, name
+                        ))?;
+
+                    default_detail.provider = engine.provider(name);
+
+                    self.wrap_with_cache(Self::schedule(
+                        Box::new(OpenAiSpeechVoice::new(client.clone(), c.clone())),
+                        &openai_limiters.get(name).cloned(),
+                    ))
+                }
+                ProfileBackendConfig::PiperVoice(c) => {
+                    let (runner, limiter) = piper.as_ref()
                         .with_context(|| format!(
-                            "Preset '{}' requires the VoiceVox backend, but it is not configured. Please verify that [backend.voicevox] exists and that 'enabled = true' and a valid 'url' are set in config.toml.",
+                            "Preset '{}' requires the Piper backend, but it is not configured. Please verify that [backend.piper] exists and 'enabled = true' in config.toml.",
                             id
-                        ))?
-                        .clone();
+                        ))?;
+                    let voice = PiperVoice::new(runner.clone(), c.clone()).with_context(|| {
+                        format!("Failed to load Piper voice of preset '{}'", id)
+                    })?;
+
+                    self.wrap_with_cache(Self::schedule(Box::new(voice), &Some(limiter.clone())))
+                }
+                ProfileBackendConfig::CommandVoice(c) => {
+                    let (timeout, limiter) = command.as_ref()
+                        .with_context(|| format!(
+                            "Preset '{}' requires the command backend, but it is not configured. Please verify that [backend.command] exists and 'enabled = true' in config.toml.",
+                            id
+                        ))?;
 
-                    self.wrap_with_cache(Box::new(VoicevoxVoice::new(client, c.clone())))
+                    self.wrap_with_cache(Self::schedule(
+                        Box::new(CommandVoice::new(c.clone(), *timeout)),
+                        &Some(limiter.clone()),
+                    ))
                 }
             };
 
-            let search_index = format!(
-                "{} {} {}",
-                detail.name,
-                detail.provider,
-                detail.description.as_deref().unwrap_or("")
-            )
-            .to_lowercase();
-
-            voices.insert(
-                id.to_string(),
-                VoicePackage {
-                    voice,
-                    detail,
-                    search_index,
-                },
-            );
+            let detail = match &profile.note {
+                Some(note) => note.resolve(default_detail),
+                None => default_detail,
+            };
+
+            voices.insert(id.to_string(), VoicePackage::new(voice, detail));
+        }
+
+        // styles which already have a hand-written profile
+        let configured_styles: HashSet<(&str, i32)> = self
+            .config
+            .profiles
+            .values()
+            .filter_map(|profile| match &profile.voice_backend {
+                ProfileBackendConfig::VoicevoxVoice(c) => Some((
+                    c.engine.as_deref().unwrap_or(DEFAULT_VOICEVOX_ENGINE),
+                    c.speaker_id,
+                )),
+                _ => None,
+            })
+            .collect();
+
+        for (name, speakers) in &self.voicevox_speakers {
+            let (engine, client) = voicevox_engines
+                .get(name)
+                .zip(self.voicevox.get(name))
+                .with_context(|| format!("VoiceVox engine '{}' is not configured", name))?;
+
+            for speaker in speakers {
+                for style in speaker.styles.iter().filter(|style| style.is_talk()) {
+                    let id = format!("{}-{}", name, style.id);
+                    if voices.contains_key(&id)
+                        || configured_styles.contains(&(name.as_str(), style.id))
+                    {
+                        continue;
+                    }
+
+                    let config = VoicevoxVoiceConfig {
+                        // same identifier as a hand-written profile of the style
+                        engine: (name != DEFAULT_VOICEVOX_ENGINE).then(|| name.clone()),
+                        speaker_id: style.id,
+                        ..Default::default()
+                    };
+                    let voice = self.wrap_with_cache(Self::schedule(
+                        Box::new(VoicevoxVoice::new(
+                            client.clone(),
+                            config,
+                            engine.language.clone(),
+                        )),
+                        &voicevox_limiters.get(name.as_str()).cloned(),
+                    ));
+                    let detail = VoiceDetail {
+                        name: format!("{} ({})", speaker.name, style.name),
+                        provider: engine.provider(name),
+                        description: None,
+                    };
+
+                    voices.insert(id, VoicePackage::new(voice, detail));
+                }
+            }
         }
 
         Ok(VoicePackageRegistry::new(voices))
     }
 
+    /// Makes the voice wait for its backend's limiter, below the cache so that hits never wait.
+    fn schedule(voice: Box<dyn Voice>, limiter: &Option<Arc<Limiter>>) -> Box<dyn Voice> {
+        match limiter {
+            Some(limiter) => Box::new(ScheduledVoice::new(voice, limiter.clone())),
+            None => voice,
+        }
+    }
+
     fn wrap_with_cache(&self, voice: Box<dyn Voice>) -> Arc<dyn Voice> {
         match &self.config.cache {
             CacheConfig::Disabled => Arc::from(voice),
@@ -174,7 +352,11 @@ mod tests {
         CacheConfig, DatabaseConfig, DatabaseKind, InMemoryCacheConfig, ProfileConfig,
         VoiceDetailConfig,
     };
+    use crate::config::{
+        ConcurrencyConfig, VoicevoxBackendConfig, VoicevoxCompatibleBackendConfig,
+    };
     use crate::tts::google_cloud::GoogleCloudVoiceConfig;
+    use crate::tts::voicevox::{Speaker, SpeakerStyle};
 
     fn create_test_config(cache: CacheConfig) -> AppConfig {
         let mut profiles = HashMap::new();
@@ -201,6 +383,8 @@ mod tests {
             },
             backend: Default::default(),
             cache,
+            session: Default::default(),
+            webhook: None,
             profiles,
         }
     }
@@ -298,6 +482,144 @@ mod tests {
         // "nonexistent" should not match
         let keywords = vec!["nonexistent"];
         let results: Vec<_> = registry.find_matching_keywords(&keywords).collect();
-        assert_eq!(results.is_empty(), true);
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn voicevox_profiles_use_their_engine() {
+        let mut config = create_test_config(CacheConfig::Disabled);
+        config.backend.voicevox_compatible.insert(
+            "aivis".to_string(),
+            VoicevoxCompatibleBackendConfig {
+                enabled: true,
+                url: "http://localhost:10101".to_string(),
+                timeout: 30,
+                provider: Some("AivisSpeech".to_string()),
+                language: "ja-JP".to_string(),
+                generate_profiles: false,
+                initialize_speakers: false,
+                concurrency: ConcurrencyConfig::default(),
+            },
+        );
+        config.profiles.insert(
+            "aivis_preset".to_string(),
+            ProfileConfig {
+                note: None,
+                voice_backend: ProfileBackendConfig::VoicevoxVoice(VoicevoxVoiceConfig {
+                    engine: Some("aivis".to_string()),
+                    speaker_id: 888753760,
+                    ..Default::default()
+                }),
+            },
+        );
+        let voicevox = voicevox::Client::new(
+            reqwest::Client::new(),
+            reqwest::Url::parse("http://localhost:10101").unwrap(),
+        );
+
+        let registry = VoicePackageRegistry::builder(config.clone())
+            .google_cloud(create_dummy_client().await)
+            .voicevox("aivis", voicevox)
+            .build()
+            .expect("Should build successfully");
+
+        let package = registry.get("aivis_preset").expect("Preset should exist");
+        assert_eq!(package.detail.provider, "AivisSpeech");
+        assert_eq!(package.voice.language(), "ja-JP");
+
+        // the engine must be registered, not only configured
+        let result = VoicePackageRegistry::builder(config)
+            .google_cloud(create_dummy_client().await)
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn profiles_are_generated_for_speaker_styles() {
+        let mut config = create_test_config(CacheConfig::Disabled);
+        config.backend.voicevox = Some(VoicevoxBackendConfig {
+            enabled: true,
+            url: "http://localhost:50021".to_string(),
+            timeout: 30,
+            generate_profiles: true,
+            initialize_speakers: false,
+            concurrency: ConcurrencyConfig::default(),
+        });
+        config.profiles.insert(
+            "zundamon".to_string(),
+            ProfileConfig {
+                note: None,
+                voice_backend: ProfileBackendConfig::VoicevoxVoice(VoicevoxVoiceConfig {
+                    speaker_id: 3,
+                    ..Default::default()
+                }),
+            },
+        );
+        let style = |name: &str, id| SpeakerStyle {
+            name: name.to_string(),
+            id,
+            kind: Some("talk".to_string()),
+        };
+        let speakers = vec![Speaker {
+            name: "ずんだもん".to_string(),
+            styles: vec![style("ノーマル", 3), style("あまあま", 1)],
+        }];
+        let voicevox = voicevox::Client::new(
+            reqwest::Client::new(),
+            reqwest::Url::parse("http://localhost:50021").unwrap(),
+        );
+
+        let registry = VoicePackageRegistry::builder(config)
+            .google_cloud(create_dummy_client().await)
+            .voicevox(DEFAULT_VOICEVOX_ENGINE, voicevox)
+            .voicevox_speakers(DEFAULT_VOICEVOX_ENGINE, speakers)
+            .build()
+            .expect("Should build successfully");
+
+        let generated = registry.get("voicevox-1").expect("style must be generated");
+        assert_eq!(generated.detail.name, "ずんだもん (あまあま)");
+        assert_eq!(generated.detail.provider, "VOICEVOX");
+
+        // covered by the hand-written profile
+        assert!(registry.get("voicevox-3").is_none());
+        assert!(registry.get("zundamon").is_some());
+    }
+
+    #[tokio::test]
+    async fn hand_written_profiles_stay_usable_without_speakers() {
+        let mut config = create_test_config(CacheConfig::Disabled);
+        config.backend.voicevox = Some(VoicevoxBackendConfig {
+            enabled: true,
+            url: "http://localhost:50021".to_string(),
+            timeout: 30,
+            generate_profiles: true,
+            initialize_speakers: false,
+            concurrency: ConcurrencyConfig::default(),
+        });
+        config.profiles.insert(
+            "zundamon".to_string(),
+            ProfileConfig {
+                note: None,
+                voice_backend: ProfileBackendConfig::VoicevoxVoice(VoicevoxVoiceConfig {
+                    speaker_id: 3,
+                    ..Default::default()
+                }),
+            },
+        );
+        let voicevox = voicevox::Client::new(
+            reqwest::Client::new(),
+            reqwest::Url::parse("http://localhost:50021").unwrap(),
+        );
+
+        // speakers could not be listed, e.g. since the engine was down at startup
+        let registry = VoicePackageRegistry::builder(config)
+            .google_cloud(create_dummy_client().await)
+            .voicevox(DEFAULT_VOICEVOX_ENGINE, voicevox)
+            .build()
+            .expect("Should build successfully");
+
+        assert!(registry.get_voice("zundamon").is_some());
+        assert!(registry.get_voice("test_preset").is_some());
+        assert!(registry.get_voice("voicevox-3").is_none());
     }
 }
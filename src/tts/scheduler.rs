@@ -0,0 +1,255 @@
+use crate::tts::{Voice, VoiceError};
+use async_trait::async_trait;
+use poise::serenity_prelude::GuildId;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+tokio::task_local! {
+    static GUILD: GuildId;
+}
+
+/// Runs `f` on behalf of the guild, so that synthesis inside is scheduled fairly against other guilds.
+///
+/// Synthesis outside of any scope shares a single slot of the per-guild limit.
+pub async fn scope<F: Future>(guild: GuildId, f: F) -> F::Output {
+    GUILD.scope(guild, f).await
+}
+
+fn current_guild() -> Option<GuildId> {
+    GUILD.try_with(|guild| *guild).ok()
+}
+
+/// Maximum number of concurrent synthesis requests to a backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimit {
+    pub total: usize,
+    pub per_guild: usize,
+}
+
+/// Limits concurrent synthesis of a backend, handing out freed slots to waiting guilds in turn.
+pub struct Limiter {
+    limit: ConcurrencyLimit,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    running_by_guild: HashMap<Option<GuildId>, usize>,
+    waiters: HashMap<Option<GuildId>, VecDeque<oneshot::Sender<Permit>>>,
+    /// guilds with waiters, in the order they get the next free slot
+    turns: VecDeque<Option<GuildId>>,
+}
+
+impl State {
+    fn running_in(&self, guild: Option<GuildId>) -> usize {
+        self.running_by_guild.get(&guild).copied().unwrap_or(0)
+    }
+
+    fn start(&mut self, guild: Option<GuildId>) {
+        self.running += 1;
+        *self.running_by_guild.entry(guild).or_default() += 1;
+    }
+
+    fn finish(&mut self, guild: Option<GuildId>) {
+        self.running -= 1;
+        if let Some(running) = self.running_by_guild.get_mut(&guild) {
+            *running -= 1;
+            if *running == 0 {
+                self.running_by_guild.remove(&guild);
+            }
+        }
+    }
+}
+
+/// Slot of a [`Limiter`], released on drop.
+pub struct Permit {
+    limiter: Option<Arc<Limiter>>,
+    guild: Option<GuildId>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            let mut state = limiter.state.lock().expect("limiter state poisoned");
+            state.finish(self.guild);
+            limiter.dispatch(&mut state);
+        }
+    }
+}
+
+impl Limiter {
+    pub fn new(limit: ConcurrencyLimit) -> Arc<Self> {
+        Arc::new(Self {
+            limit: ConcurrencyLimit {
+                total: limit.total.max(1),
+                per_guild: limit.per_guild.max(1),
+            },
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    /// Waits for a free slot. Guilds waiting earlier are not overtaken.
+    pub async fn acquire(self: &Arc<Self>, guild: Option<GuildId>) -> Permit {
+        let rx = {
+            let mut state = self.state.lock().expect("limiter state poisoned");
+            if !state.waiters.contains_key(&guild) && self.has_room(&state, guild) {
+                state.start(guild);
+                return Permit {
+                    limiter: Some(self.clone()),
+                    guild,
+                };
+            }
+
+            let (tx, rx) = oneshot::channel();
+            if !state.waiters.contains_key(&guild) {
+                state.turns.push_back(guild);
+            }
+            state.waiters.entry(guild).or_default().push_back(tx);
+            rx
+        };
+
+        rx.await.expect("limiter must not drop waiters")
+    }
+
+    fn has_room(&self, state: &State, guild: Option<GuildId>) -> bool {
+        state.running < self.limit.total && state.running_in(guild) < self.limit.per_guild
+    }
+
+    /// Hands out free slots to the guilds in turn, skipping those at their own limit.
+    fn dispatch(self: &Arc<Self>, state: &mut State) {
+        let mut skipped = 0;
+        while state.running < self.limit.total && skipped < state.turns.len() {
+            let guild = state.turns.pop_front().expect("checked by skipped < len");
+            if !self.has_room(state, guild) {
+                state.turns.push_back(guild);
+                skipped += 1;
+                continue;
+            }
+            skipped = 0;
+
+            let waiters = state
+                .waiters
+                .get_mut(&guild)
+                .expect("guild in turns has waiters");
+            let tx = waiters.pop_front().expect("waiters are never left empty");
+            let remaining = !waiters.is_empty();
+            if remaining {
+                state.turns.push_back(guild);
+            } else {
+                state.waiters.remove(&guild);
+            }
+
+            state.start(guild);
+            let permit = Permit {
+                limiter: Some(self.clone()),
+                guild,
+            };
+            if let Err(mut permit) = tx.send(permit) {
+                // the waiter gave up, so the slot goes to the next one
+                permit.limiter = None;
+                state.finish(guild);
+            }
+        }
+    }
+}
+
+/// Voice waiting for a slot of its backend before synthesizing.
+pub struct ScheduledVoice {
+    inner: Box<dyn Voice>,
+    limiter: Arc<Limiter>,
+}
+
+impl ScheduledVoice {
+    pub fn new(inner: Box<dyn Voice>, limiter: Arc<Limiter>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+#[async_trait]
+impl Voice for ScheduledVoice {
+    fn identifier(&self) -> &str {
+        self.inner.identifier()
+    }
+
+    fn language(&self) -> &str {
+        self.inner.language()
+    }
+
+    async fn generate(&self, text: &str) -> Result<Vec<u8>, VoiceError> {
+        let _permit = self.limiter.acquire(current_guild()).await;
+        self.inner.generate(text).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const A: Option<GuildId> = Some(GuildId::new(1));
+    const B: Option<GuildId> = Some(GuildId::new(2));
+
+    fn limiter(total: usize, per_guild: usize) -> Arc<Limiter> {
+        Limiter::new(ConcurrencyLimit { total, per_guild })
+    }
+
+    async fn is_blocked(limiter: &Arc<Limiter>, guild: Option<GuildId>) -> bool {
+        tokio::time::timeout(Duration::from_millis(50), limiter.acquire(guild))
+            .await
+            .is_err()
+    }
+
+    #[tokio::test]
+    async fn limits_total_and_per_guild_concurrency() {
+        let limiter = limiter(3, 2);
+
+        let _a1 = limiter.acquire(A).await;
+        let _a2 = limiter.acquire(A).await;
+        assert!(is_blocked(&limiter, A).await);
+
+        let _b1 = limiter.acquire(B).await;
+        assert!(is_blocked(&limiter, B).await);
+    }
+
+    #[tokio::test]
+    async fn freed_slots_alternate_between_waiting_guilds() {
+        let limiter = limiter(1, 1);
+        let running = limiter.acquire(None).await;
+
+        let (order_tx, mut order_rx) = tokio::sync::mpsc::unbounded_channel();
+        for (guild, label) in [(A, "a1"), (A, "a2"), (A, "a3"), (B, "b1"), (B, "b2")] {
+            let limiter = limiter.clone();
+            let order_tx = order_tx.clone();
+            tokio::spawn(async move {
+                let _permit = limiter.acquire(guild).await;
+                order_tx.send(label).unwrap();
+            });
+            // queue the waiters in the listed order
+            tokio::task::yield_now().await;
+        }
+        drop(order_tx);
+
+        drop(running);
+        let mut order = Vec::new();
+        while let Some(label) = order_rx.recv().await {
+            order.push(label);
+        }
+
+        assert_eq!(order, vec!["a1", "b1", "a2", "b2", "a3"]);
+    }
+
+    #[tokio::test]
+    async fn abandoned_waiters_do_not_hold_slots() {
+        let limiter = limiter(1, 1);
+        let running = limiter.acquire(A).await;
+
+        assert!(is_blocked(&limiter, B).await);
+        drop(running);
+
+        let _b = limiter.acquire(B).await;
+        assert!(is_blocked(&limiter, A).await);
+    }
+}
@@ -52,6 +52,28 @@ impl LazyAudioQuery {
     }
 }
 
+/// Character of the engine, listed by `/speakers`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Speaker {
+    pub name: String,
+    pub styles: Vec<SpeakerStyle>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeakerStyle {
+    pub name: String,
+    pub id: i32,
+    /// `talk` for speech, absent on older engines. Singing styles cannot read text.
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+}
+
+impl SpeakerStyle {
+    pub fn is_talk(&self) -> bool {
+        self.kind.as_deref().is_none_or(|kind| kind == "talk")
+    }
+}
+
 /// minimum client for Voicevox
 #[derive(Clone)]
 pub struct Client {
@@ -64,6 +86,35 @@ impl Client {
         Client { http, base_url }
     }
 
+    pub async fn speakers(&self) -> anyhow::Result<Vec<Speaker>> {
+        let url = self.base_url.join("/speakers")?;
+        let res = self
+            .http
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Loads the model of the style, so that the first synthesis is not slow.
+    pub async fn initialize_speaker(&self, speaker: i32) -> anyhow::Result<()> {
+        let url = self.base_url.join("/initialize_speaker")?;
+        self.http
+            .post(url)
+            .query(&[
+                ("speaker", speaker.to_string().as_str()),
+                ("skip_reinit", "true"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
     async fn audio_query(&self, text: &str, speaker: i32) -> anyhow::Result<LazyAudioQuery> {
         let url = self.base_url.join("/audio_query")?;
         let res = self
@@ -100,8 +151,11 @@ impl Client {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct VoicevoxVoiceConfig {
+    /// Name of the engine under `[backend.voicevox_compatible]`, `[backend.voicevox]` if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
     pub speaker_id: i32,
     pub speed_scale: Option<f64>,
     pub pitch_scale: Option<f64>,
@@ -125,15 +179,17 @@ pub struct VoicevoxVoice {
     identifier: String,
     client: Client,
     config: VoicevoxVoiceConfig,
+    language: String,
 }
 
 impl VoicevoxVoice {
-    pub fn new(client: Client, config: VoicevoxVoiceConfig) -> VoicevoxVoice {
+    pub fn new(client: Client, config: VoicevoxVoiceConfig, language: String) -> VoicevoxVoice {
         let identifier = Self::build_identifier(&config);
         Self {
             identifier,
             client,
             config,
+            language,
         }
     }
 
@@ -152,7 +208,7 @@ impl Voice for VoicevoxVoice {
     }
 
     fn language(&self) -> &str {
-        "ja-JP"
+        &self.language
     }
 
     async fn generate(&self, text: &str) -> Result<Vec<u8>, VoiceError> {
@@ -173,3 +229,33 @@ impl Voice for VoicevoxVoice {
         Ok(res_synthesis)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speakers_keep_only_talk_styles() {
+        let speakers: Vec<Speaker> = serde_json::from_str(
+            r#"[{
+                "name": "四国めたん",
+                "speaker_uuid": "7ffcb7ce-00ec-4bdc-82cd-45a8889e43ff",
+                "styles": [
+                    {"name": "ノーマル", "id": 2, "type": "talk"},
+                    {"name": "あまあま", "id": 0},
+                    {"name": "ハミング", "id": 3001, "type": "frame_decode"}
+                ],
+                "version": "0.15.0"
+            }]"#,
+        )
+        .unwrap();
+
+        let talk: Vec<i32> = speakers[0]
+            .styles
+            .iter()
+            .filter(|style| style.is_talk())
+            .map(|style| style.id)
+            .collect();
+        assert_eq!(talk, vec![2, 0]);
+    }
+}
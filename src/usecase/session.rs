@@ -1,9 +1,12 @@
 use crate::handler::Data;
-use crate::session::actor::SessionActor;
+use crate::session::ReadingMode;
+use crate::session::actor::{IdleTimeout, SessionActor};
 use crate::session::driver::SongbirdDriver;
+use crate::session::event::SessionEventKind;
+use crate::session::notifier::DiscordNotifier;
 use anyhow::Context;
 use poise::serenity_prelude as serenity;
-use poise::serenity_prelude::{ChannelId, GuildId};
+use poise::serenity_prelude::{Builder, ChannelId, ChannelType, EditVoiceState, GuildId};
 use std::sync::Arc;
 
 pub async fn start(
@@ -12,6 +15,7 @@ pub async fn start(
     guild_id: GuildId,
     text_channel_id: ChannelId,
     voice_channel_id: ChannelId,
+    reading_mode: ReadingMode,
 ) -> anyhow::Result<()> {
     let manager = songbird::get(ctx)
         .await
@@ -23,14 +27,23 @@ pub async fn start(
         .await
         .context("Failed to join voice channel")?;
 
-    // prepare session actor to start text-to-speech
-    let driver = SongbirdDriver { call: handler };
-    let (actor, handle) = SessionActor::new(Arc::new(driver));
+    if is_stage_channel(ctx, guild_id, voice_channel_id) {
+        become_speaker(ctx, guild_id, voice_channel_id).await;
+    }
 
-    tokio::spawn(actor.run());
+    // notices are posted in the guild's preferred locale
+    let locale = guild_id
+        .to_guild_cached(&ctx.cache)
+        .map(|guild| guild.preferred_locale.clone())
+        .unwrap_or_else(|| "en-US".to_owned());
+    let notifier = DiscordNotifier::new(
+        ctx.http.clone(),
+        text_channel_id,
+        locale,
+        data.discord_locales.clone(),
+    );
 
-    data.session_manager
-        .register(guild_id, text_channel_id, voice_channel_id, handle.clone());
+    let settings = data.settings_repository.find(guild_id).await?;
 
     let profile = data.resolver.resolve_guild_with_fallback(guild_id).await;
 
@@ -39,7 +52,54 @@ pub async fn start(
         Err(_) => data.resolver.fallback(),
     };
 
-    let voice = data.registry.get_voice(profile_str).unwrap();
+    // generated profiles are missing while their engine is unreachable
+    let voice = match data.registry.get_voice(profile_str) {
+        Some(voice) => voice,
+        None => {
+            tracing::warn!("No voice for profile {}, using the fallback", profile_str);
+            data.registry
+                .get_voice(data.resolver.fallback())
+                .ok_or_else(|| anyhow::anyhow!("No voice preset found"))?
+        }
+    };
+
+    let idle_timeout = IdleTimeout {
+        after: settings.idle_timeout(),
+        announcement: data
+            .tts_locales
+            .resolve(voice.language(), "idle-leave", None, None)?,
+        voice: voice.clone(),
+    };
+
+    // prepare session actor to start text-to-speech
+    let driver = SongbirdDriver::new(handler, settings.volume_ratio());
+    let (actor, handle) = SessionActor::new(
+        Arc::new(driver),
+        &data.session_config,
+        settings.name_announcement(),
+        idle_timeout,
+        Arc::new(notifier),
+        data.events.for_guild(guild_id),
+    );
+
+    tokio::spawn(actor.run());
+
+    data.session_manager
+        .register(
+            guild_id,
+            text_channel_id,
+            voice_channel_id,
+            reading_mode,
+            handle.clone(),
+        )
+        .await;
+
+    data.events
+        .for_guild(guild_id)
+        .emit(SessionEventKind::Started {
+            voice_channel_id,
+            text_channel_id,
+        });
 
     handle
         .announce(
@@ -51,3 +111,63 @@ pub async fn start(
 
     Ok(())
 }
+
+/// Moves the bot of a running session to another voice channel.
+///
+/// Indexes of the session are updated once the voice state of the bot changes.
+pub async fn move_to(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    voice_channel_id: ChannelId,
+) -> anyhow::Result<()> {
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Songbird Voice client not initialized"))?
+        .clone();
+
+    manager
+        .join(guild_id, voice_channel_id)
+        .await
+        .context("Failed to move to voice channel")?;
+
+    Ok(())
+}
+
+pub fn is_stage_channel(ctx: &serenity::Context, guild_id: GuildId, channel_id: ChannelId) -> bool {
+    guild_id
+        .to_guild_cached(&ctx.cache)
+        .and_then(|guild| {
+            guild
+                .channels
+                .get(&channel_id)
+                .map(|channel| channel.kind == ChannelType::Stage)
+        })
+        .unwrap_or(false)
+}
+
+/// Joins a stage channel as a speaker, since the audience cannot be heard.
+///
+/// Becomes a speaker directly if allowed to, and raises a hand for moderators otherwise.
+pub async fn become_speaker(ctx: &serenity::Context, guild_id: GuildId, channel_id: ChannelId) {
+    let unsuppressed = EditVoiceState::new()
+        .suppress(false)
+        .execute(ctx, (guild_id, channel_id, None))
+        .await;
+
+    let Err(err) = unsuppressed else {
+        return;
+    };
+    tracing::debug!("Could not become a speaker, requesting to speak: {:?}", err);
+
+    if let Err(err) = EditVoiceState::new()
+        .request_to_speak(true)
+        .execute(ctx, (guild_id, channel_id, None))
+        .await
+    {
+        tracing::warn!(
+            "Failed to request to speak in stage channel {}: {:?}",
+            channel_id,
+            err
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use dashmap::DashMap;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Holds back leave notifications, so that a user who leaves and comes right back
+/// produces neither a leave nor a join notification.
+///
+/// A leave is started with [`LeaveDebouncer::leave`], and announced only if
+/// [`LeaveDebouncer::settle`] still finds it pending after the debounce period.
+#[derive(Default)]
+pub struct LeaveDebouncer {
+    /// token of the latest leave from each channel
+    pending: DashMap<(GuildId, UserId, ChannelId), u64>,
+    next_token: AtomicU64,
+}
+
+impl LeaveDebouncer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a leave from `channel`. Returns the token to pass to [`LeaveDebouncer::settle`].
+    pub fn leave(&self, guild: GuildId, user: UserId, channel: ChannelId) -> u64 {
+        let token = self.next_token.fetch_add(1, Ordering::Relaxed);
+        self.pending.insert((guild, user, channel), token);
+        token
+    }
+
+    /// Records a join to `channel`. Returns true if it cancelled a pending leave from the same
+    /// channel, in which case the join should not be announced either.
+    pub fn join(&self, guild: GuildId, user: UserId, channel: ChannelId) -> bool {
+        self.pending.remove(&(guild, user, channel)).is_some()
+    }
+
+    /// Finishes the leave from `channel` started with `token`. Returns true if it should be announced.
+    pub fn settle(&self, guild: GuildId, user: UserId, channel: ChannelId, token: u64) -> bool {
+        self.pending
+            .remove_if(&(guild, user, channel), |_, pending| *pending == token)
+            .is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: GuildId = GuildId::new(1);
+    const USER: UserId = UserId::new(2);
+    const CHANNEL: ChannelId = ChannelId::new(3);
+
+    #[test]
+    fn leave_is_announced_unless_user_rejoins() {
+        let debouncer = LeaveDebouncer::new();
+
+        let token = debouncer.leave(GUILD, USER, CHANNEL);
+        assert!(debouncer.settle(GUILD, USER, CHANNEL, token));
+
+        let token = debouncer.leave(GUILD, USER, CHANNEL);
+        assert!(debouncer.join(GUILD, USER, CHANNEL));
+        assert!(!debouncer.settle(GUILD, USER, CHANNEL, token));
+    }
+
+    #[test]
+    fn joining_another_channel_does_not_cancel_leave() {
+        let debouncer = LeaveDebouncer::new();
+
+        let token = debouncer.leave(GUILD, USER, CHANNEL);
+
+        assert!(!debouncer.join(GUILD, USER, ChannelId::new(4)));
+        assert!(debouncer.settle(GUILD, USER, CHANNEL, token));
+    }
+
+    #[test]
+    fn only_the_latest_leave_is_announced() {
+        let debouncer = LeaveDebouncer::new();
+
+        let first = debouncer.leave(GUILD, USER, CHANNEL);
+        let second = debouncer.leave(GUILD, USER, CHANNEL);
+
+        assert!(!debouncer.settle(GUILD, USER, CHANNEL, first));
+        assert!(debouncer.settle(GUILD, USER, CHANNEL, second));
+    }
+
+    #[test]
+    fn moving_away_and_back_cancels_only_the_first_leave() {
+        let debouncer = LeaveDebouncer::new();
+        let other = ChannelId::new(4);
+
+        let first = debouncer.leave(GUILD, USER, CHANNEL);
+        assert!(!debouncer.join(GUILD, USER, other));
+        let second = debouncer.leave(GUILD, USER, other);
+        assert!(debouncer.join(GUILD, USER, CHANNEL));
+
+        assert!(!debouncer.settle(GUILD, USER, CHANNEL, first));
+        assert!(debouncer.settle(GUILD, USER, other, second));
+    }
+}